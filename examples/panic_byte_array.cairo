@@ -0,0 +1,7 @@
+fn main() -> Array<felt252> {
+    let value: u32 = 1234;
+    assert!(value < 1000, "Value {} is too large", value);
+    let mut output: Array<felt252> = ArrayTrait::new();
+    value.serialize(ref output);
+    output
+}
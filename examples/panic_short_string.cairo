@@ -0,0 +1,9 @@
+fn main() -> Array<felt252> {
+    let values: Array<felt252> = array![1, 2, 3];
+    if values.len() < 4 {
+        panic_with_felt252('Index out of bounds');
+    }
+    let mut output: Array<felt252> = ArrayTrait::new();
+    values.serialize(ref output);
+    output
+}
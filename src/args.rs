@@ -19,8 +19,10 @@ use std::path::PathBuf;
 pub enum Cli {
     Prove(ProveArgs),
     ProveBootloader(ProveBootloaderArgs),
+    Params(ParamsArgs),
     Verify(VerifyArgs),
     SerializeProof(SerializeArgs),
+    CheckCompat(CheckCompatArgs),
 }
 
 #[derive(Args, Debug)]
@@ -29,8 +31,20 @@ pub struct ProveArgs {
     #[clap(long = "cairo_version", value_enum, default_value = "cairo1")]
     pub cairo_version: CairoVersion,
 
-    #[clap(long = "cairo_program", value_hint=ValueHint::FilePath)]
-    pub cairo_program: PathBuf,
+    #[clap(
+        long = "cairo_program",
+        value_hint=ValueHint::FilePath,
+        required_unless_present = "cairo_pie"
+    )]
+    pub cairo_program: Option<PathBuf>,
+
+    #[clap(
+        long = "cairo_pie",
+        value_hint=ValueHint::FilePath,
+        help = "Cairo PIE zip file to prove. The PIE is run as the single task of the bootloader, so the proof is a proof of the bootloader running the PIE, not of the PIE's program",
+        conflicts_with_all = ["cairo_program", "program_input", "program_input_file"]
+    )]
+    pub cairo_pie: Option<PathBuf>,
 
     #[clap(
         long = "program_input",
@@ -79,14 +93,41 @@ pub struct ProveArgs {
             "log_n_cosets",
             "use_extension_field",
             "verifier_friendly_channel_updates",
-            "verifier_friendly_commitment_hash"
+            "verifier_friendly_commitment_hash",
+            "fri_strategy",
+            "fri_max_step"
         ]
     )]
     pub parameter_file: Option<PathBuf>,
 
+    #[clap(
+        long = "preset",
+        value_enum,
+        conflicts_with_all = [
+            "parameter_file",
+            "channel_hash",
+            "commitment_hash",
+            "n_verifier_friendly_commitment_layers",
+            "pow_hash",
+            "verifier_friendly_channel_updates",
+            "verifier_friendly_commitment_hash",
+            "stone_version"
+        ],
+        help = "Verifier the proof is meant for, which sets the hashes and the Stone version it accepts and restricts the layout"
+    )]
+    pub preset: Option<Preset>,
+
     #[clap(long = "output", default_value = "./proof.json")]
     pub output: PathBuf,
 
+    #[clap(
+        long = "cairo_pie_output",
+        value_hint=ValueHint::FilePath,
+        help = "Run the program in non-proof mode and write its Cairo PIE to this zip file instead of creating a proof. The PIE can later be proved with prove-bootloader",
+        conflicts_with = "cairo_pie"
+    )]
+    pub cairo_pie_output: Option<PathBuf>,
+
     #[clap(flatten)]
     pub parameter_config: ProverParametersConfig,
 
@@ -118,9 +159,54 @@ pub struct ProveBootloaderArgs {
     )]
     pub cairo_pies: Option<Vec<PathBuf>>,
 
+    #[clap(
+        long = "cairo1_programs",
+        value_hint=ValueHint::FilePath,
+        value_delimiter = ' ',
+        num_args = 1..,
+        help = "Cairo 1 source or Sierra files, which are run to Cairo PIEs before being added as tasks"
+    )]
+    pub cairo1_programs: Option<Vec<PathBuf>>,
+
+    #[clap(
+        long = "cairo1_program_input_files",
+        value_hint=ValueHint::FilePath,
+        value_delimiter = ' ',
+        num_args = 1..,
+        requires = "cairo1_programs",
+        help = "Input file for each of the Cairo 1 programs, in the same order as --cairo1_programs"
+    )]
+    pub cairo1_program_input_files: Option<Vec<PathBuf>>,
+
+    #[clap(
+        long = "tasks",
+        value_hint=ValueHint::FilePath,
+        conflicts_with_all = ["cairo_programs", "cairo_pies", "cairo1_programs"],
+        help = "JSON or TOML manifest listing the tasks with their input file, label and expected output"
+    )]
+    pub tasks: Option<PathBuf>,
+
+    #[clap(
+        long = "task_report_output",
+        value_hint=ValueHint::FilePath,
+        help = "Output file for a JSON report with the label, program hash and output of each task"
+    )]
+    pub task_report_output: Option<PathBuf>,
+
     #[clap(long = "layout", default_value = "starknet", value_enum)]
     pub layout: LayoutName,
 
+    #[clap(long = "stone_version", default_value = "v5", value_enum)]
+    pub stone_version: StoneVersion,
+
+    #[clap(
+        long = "verifier_target",
+        default_value = "ethereum",
+        value_enum,
+        help = "Verifier the proof is meant for, used to reject layout and Stone version combinations it does not accept"
+    )]
+    pub verifier_target: VerifierTarget,
+
     #[clap(
         long = "prover_config_file",
         conflicts_with_all = [
@@ -149,11 +235,31 @@ pub struct ProveBootloaderArgs {
             "log_n_cosets",
             "use_extension_field",
             "verifier_friendly_channel_updates",
-            "verifier_friendly_commitment_hash"
+            "verifier_friendly_commitment_hash",
+            "fri_strategy",
+            "fri_max_step"
         ]
     )]
     pub parameter_file: Option<PathBuf>,
 
+    #[clap(
+        long = "preset",
+        value_enum,
+        conflicts_with_all = [
+            "parameter_file",
+            "channel_hash",
+            "commitment_hash",
+            "n_verifier_friendly_commitment_layers",
+            "pow_hash",
+            "verifier_friendly_channel_updates",
+            "verifier_friendly_commitment_hash",
+            "stone_version",
+            "verifier_target"
+        ],
+        help = "Verifier the proof is meant for, which sets the hashes and the Stone version it accepts and restricts the layout"
+    )]
+    pub preset: Option<Preset>,
+
     #[clap(long = "output", default_value = "./bootloader_proof.json")]
     pub output: PathBuf,
 
@@ -177,10 +283,106 @@ pub struct ProveBootloaderArgs {
     )]
     pub ignore_fact_topologies: bool,
 
+    #[clap(
+        long = "bootloader_version",
+        default_value = "v0-13-1",
+        value_enum,
+        help = "Built-in bootloader version, which also selects the default supported program hashes"
+    )]
+    pub bootloader_version: BootloaderVersion,
+
+    #[clap(
+        long = "bootloader_program",
+        value_hint=ValueHint::FilePath,
+        help = "Compiled bootloader program to use instead of the built-in one"
+    )]
+    pub bootloader_program: Option<PathBuf>,
+
+    #[clap(
+        long = "bootloader_config",
+        value_hint=ValueHint::FilePath,
+        help = "JSON file with the simple bootloader program hash and the supported Cairo verifier program hashes"
+    )]
+    pub bootloader_config: Option<PathBuf>,
+
+    #[clap(
+        long = "single_page",
+        help = "Write the outputs of all tasks to a single public memory page"
+    )]
+    pub single_page: bool,
+
     #[clap(long = "bench_memory")]
     pub bench_memory: Option<bool>,
 }
 
+#[derive(Args, Debug)]
+pub struct ParamsArgs {
+    #[clap(
+        long = "n_steps",
+        required_unless_present = "cairo_program",
+        conflicts_with = "cairo_program",
+        help = "Number of Cairo steps of the program to prove"
+    )]
+    pub n_steps: Option<u32>,
+
+    #[clap(
+        long = "cairo_program",
+        value_hint=ValueHint::FilePath,
+        help = "Program that is run to get the number of steps"
+    )]
+    pub cairo_program: Option<PathBuf>,
+
+    #[clap(long = "cairo_version", value_enum, default_value = "cairo1")]
+    pub cairo_version: CairoVersion,
+
+    #[clap(
+        long = "program_input",
+        default_value = "",
+        value_parser = process_args,
+        help = "Arguments should be spaced, with array elements placed between brackets, e.g. '1 2 [1 2 3]'",
+        conflicts_with = "program_input_file"
+    )]
+    pub program_input: FuncArgs,
+
+    #[clap(
+        long = "program_input_file",
+        value_hint=ValueHint::FilePath,
+        conflicts_with="program_input"
+    )]
+    pub program_input_file: Option<PathBuf>,
+
+    #[clap(long = "layout", default_value = "recursive", value_enum)]
+    pub layout: LayoutName,
+
+    #[clap(
+        long = "security_bits",
+        default_value = "96",
+        help = "Target conjectured security, computed as n_queries * log_n_cosets + proof_of_work_bits"
+    )]
+    pub security_bits: u32,
+
+    #[clap(long = "n_queries", help = "Solved for if not provided")]
+    pub n_queries: Option<u32>,
+
+    #[clap(
+        long = "log_n_cosets",
+        help = "Defaults to 4 unless solved for with --n_queries, up to 16"
+    )]
+    pub log_n_cosets: Option<u32>,
+
+    #[clap(
+        long = "proof_of_work_bits",
+        help = "Defaults to 32, or the target security if lower"
+    )]
+    pub proof_of_work_bits: Option<u32>,
+
+    #[clap(long = "last_layer_degree_bound", help = "Defaults to 64")]
+    pub last_layer_degree_bound: Option<u32>,
+
+    #[clap(long = "output", default_value = "./cpu_air_params.json")]
+    pub output: PathBuf,
+}
+
 #[derive(Args, Debug)]
 pub struct VerifyArgs {
     #[clap(long = "proof", value_parser)]
@@ -299,12 +501,33 @@ impl LayoutName {
     }
 }
 
+define_enum! {
+    BootloaderVersion,
+    v0_13_1 => "0.13.1",
+}
+
 define_enum! {
     StoneVersion,
     V5 => "V5",
     V6 => "V6",
 }
 
+define_enum! {
+    Preset,
+    ethereum => "ethereum",
+    integrity_keccak160_stone5 => "integrity_keccak160_stone5",
+    integrity_keccak160_stone6 => "integrity_keccak160_stone6",
+    integrity_blake248_stone5 => "integrity_blake248_stone5",
+    integrity_blake248_stone6 => "integrity_blake248_stone6",
+}
+
+define_enum! {
+    VerifierTarget,
+    ethereum => "ethereum",
+    integrity => "integrity",
+    stone => "stone",
+}
+
 #[derive(Args, Debug, Clone)]
 pub struct SerializeArgs {
     #[clap(long = "proof", value_hint=ValueHint::FilePath)]
@@ -316,14 +539,13 @@ pub struct SerializeArgs {
     #[clap(long = "output", value_hint=ValueHint::FilePath, required_if_eq_any([("serialization_type", "monolith"), ("network", "ethereum")]))]
     pub output: Option<PathBuf>,
 
-    #[clap(long = "output_dir", value_hint=ValueHint::DirPath, help="Output directory for storing split proof files. Required for creating split proofs for Starknet", required_if_eq("serialization_type", "split"))]
+    #[clap(long = "output_dir", value_hint=ValueHint::DirPath, help="Output directory for storing split proof files. Required for creating split proofs for Starknet and Ethereum", required_if_eq("serialization_type", "split"))]
     pub output_dir: Option<PathBuf>,
 
     #[clap(
         long = "layout",
-        help = "Only required for creating split proofs for Starknet",
-        value_enum,
-        required_if_eq("serialization_type", "split")
+        help = "Layout of the proof for Starknet. Read from the proof if not given, and rejected if it differs from the layout of the proof",
+        value_enum
     )]
     pub layout: Option<LayoutName>,
 
@@ -350,6 +572,98 @@ pub struct SerializeArgs {
         required_if_eq("network", "starknet")
     )]
     pub serialization_type: Option<SerializationType>,
+
+    #[clap(
+        long = "cairo_version",
+        help = "Cairo version of the proven program, which selects the memory verification mode of the Integrity verifier. Defaults to cairo0",
+        value_enum
+    )]
+    pub cairo_version: Option<CairoVersion>,
+
+    #[clap(
+        long = "memory_verification",
+        help = "Memory verification mode of the Integrity verifier. Defaults to strict for Cairo 0 programs and must be cairo1 for Cairo 1 programs",
+        value_enum
+    )]
+    pub memory_verification: Option<MemoryVerification>,
+
+    #[clap(
+        long = "stone_version",
+        help = "Stone version used to create the proof. Read from the commit recorded in the proof, and rejected if it differs from it. Defaults to v6, the default of prove, if the proof records no commit",
+        value_enum
+    )]
+    pub stone_version: Option<StoneVersion>,
+
+    #[clap(
+        long = "format",
+        help = "Format of the Starknet calldata files. Defaults to decimal for monolith proofs and hex for split proofs. The sncast format prepends the job id of split proofs so that each file can be passed to `sncast invoke --calldata`",
+        value_enum,
+        conflicts_with = "split"
+    )]
+    pub format: Option<CalldataFormat>,
+
+    #[clap(
+        long = "job_id",
+        help = "Job id of split proofs, passed to every verifier call. A random job id is used if not given",
+        requires = "output_dir"
+    )]
+    pub job_id: Option<u64>,
+
+    #[clap(
+        long = "manifest",
+        help = "Output file listing the Starknet calls in submission order. Defaults to manifest.json in --output_dir for split proofs",
+        value_hint=ValueHint::FilePath,
+        conflicts_with = "split"
+    )]
+    pub manifest: Option<PathBuf>,
+
+    #[clap(
+        long = "split",
+        help = "Write the calldata of every transaction needed to verify the proof on Ethereum to --output_dir, in submission order",
+        conflicts_with = "serialization_type",
+        requires_all = ["output_dir", "fact_topologies_file"]
+    )]
+    pub split: bool,
+
+    #[clap(
+        long = "fact_topologies_file",
+        help = "Fact topologies file written by prove-bootloader. Required for --split",
+        value_hint=ValueHint::FilePath
+    )]
+    pub fact_topologies_file: Option<PathBuf>,
+
+    #[clap(
+        long = "check",
+        help = "Decode the written Starknet calldata and check that it encodes the proof. For split proofs, only the initial call is decoded",
+        conflicts_with = "split"
+    )]
+    pub check: bool,
+
+    #[clap(
+        long = "report",
+        help = "Print the size and estimated calldata cost of every transaction, whether it exceeds the calldata limit of the network, and prover parameters that would shrink the proof. Requires --split for Ethereum"
+    )]
+    pub report: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CheckCompatArgs {
+    #[clap(long = "proof", value_hint=ValueHint::FilePath)]
+    pub proof: PathBuf,
+
+    #[clap(
+        long = "stone_version",
+        value_enum,
+        help = "Stone version used to create the proof. Otherwise read from the commit recorded in the proof, or found by checking the proof with the v5 and v6 verifiers"
+    )]
+    pub stone_version: Option<StoneVersion>,
+
+    #[clap(
+        long = "output",
+        value_hint=ValueHint::FilePath,
+        help = "Output file for the compatibility report as JSON"
+    )]
+    pub output: Option<PathBuf>,
 }
 
 define_enum! {
@@ -363,3 +677,18 @@ define_enum! {
     monolith => "monolith",
     split => "split",
 }
+
+define_enum! {
+    MemoryVerification,
+    strict => "strict",
+    relaxed => "relaxed",
+    cairo1 => "cairo1",
+}
+
+define_enum! {
+    CalldataFormat,
+    json => "json",
+    hex => "hex",
+    decimal => "decimal",
+    sncast => "sncast",
+}
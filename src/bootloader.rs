@@ -1,28 +1,38 @@
-use crate::args::ProveBootloaderArgs;
-use crate::utils::{get_formatted_air_public_input, FileWriter};
+use crate::args::{BootloaderVersion, LayoutName, ProveBootloaderArgs};
+use crate::cairo::{run_cairo1_program, write_cairo_pie, DYNAMIC_LAYOUT};
+use crate::manifest::{load_task_manifest, TaskEntry, TaskKind, TaskManifest};
+use crate::target::check_bootloader_target;
+use crate::utils::{
+    get_formatted_air_public_input, parse_felt, process_args, write_json_to_file, FileWriter,
+    FuncArgs,
+};
 use cairo_bootloader::hints::{
     BootloaderConfig, BootloaderHintProcessor, BootloaderInput, PackedOutput,
     SimpleBootloaderInput, TaskSpec,
 };
 use cairo_bootloader::insert_bootloader_input;
 use cairo_bootloader::tasks::{make_bootloader_tasks, BootloaderTaskError};
-use cairo_vm::air_public_input::PublicInputError;
+use cairo_vm::air_public_input::{PublicInputError, PublicMemoryEntry};
 use cairo_vm::cairo_run::{
     cairo_run_program_with_initial_scope, write_encoded_memory, write_encoded_trace,
     CairoRunConfig, EncodeTraceError,
 };
+use cairo_vm::program_hash::{compute_program_hash_chain, ProgramHashError};
 use cairo_vm::types::errors::program_errors::ProgramError;
 use cairo_vm::types::exec_scope::ExecutionScopes;
-use cairo_vm::types::layout_name::LayoutName;
+use cairo_vm::types::layout::CairoLayoutParams;
 use cairo_vm::types::program::Program;
+use cairo_vm::types::relocatable::Relocatable;
 use cairo_vm::vm::errors::cairo_run_errors::CairoRunError;
 use cairo_vm::vm::errors::trace_errors::TraceError;
 use cairo_vm::vm::errors::vm_errors::VirtualMachineError;
 use cairo_vm::vm::runners::cairo_runner::CairoRunner;
 use cairo_vm::Felt252;
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 const BOOTLOADER_V0_13_1: &[u8] = include_bytes!("../resources/bootloader-0.13.1.json");
@@ -51,6 +61,30 @@ const CAIRO_VERIFIER_PROGRAM_HASHES: &[&str] = &[
     "0x323c8251dbd935f45105bc241765a5082d9a985cbc3bffece3382708fb88dc5",
 ];
 
+/// A bootloader release shipped with the CLI, along with the program hashes it is deployed with
+struct KnownBootloader {
+    version: BootloaderVersion,
+    program: &'static [u8],
+    simple_bootloader_program_hash: &'static str,
+    cairo_verifier_program_hashes: &'static [&'static str],
+}
+
+const KNOWN_BOOTLOADERS: &[KnownBootloader] = &[KnownBootloader {
+    version: BootloaderVersion::v0_13_1,
+    program: BOOTLOADER_V0_13_1,
+    simple_bootloader_program_hash: SIMPLE_BOOTLOADER_PROGRAM_HASH,
+    cairo_verifier_program_hashes: CAIRO_VERIFIER_PROGRAM_HASHES,
+}];
+
+/// Contents of the file given with `--bootloader_config`
+///
+/// Hashes can be given either as hex strings prefixed with `0x` or as decimal strings.
+#[derive(Debug, Deserialize)]
+pub struct BootloaderHashesConfig {
+    pub simple_bootloader_program_hash: String,
+    pub supported_cairo_verifier_program_hashes: Vec<String>,
+}
+
 pub struct CairoBootloaderRunResult {
     pub air_public_input: PathBuf,
     pub air_private_input: PathBuf,
@@ -80,6 +114,46 @@ pub enum Error {
     VirtualMachine(#[from] VirtualMachineError),
     #[error("Topology file should be specified as it will be required for serializing bootloader proofs")]
     TopologyFileNotSpecified,
+    #[error(
+        "Expected an input file for each of the {programs} Cairo 1 programs, got {input_files}"
+    )]
+    Cairo1ProgramInputFilesMismatch { programs: usize, input_files: usize },
+    #[error("Failed to run Cairo 1 program {0:?}: {1}")]
+    Cairo1Program(PathBuf, anyhow::Error),
+    #[error(transparent)]
+    ProgramHash(#[from] ProgramHashError),
+    #[error("Invalid program hash {0:?} in the bootloader config")]
+    InvalidProgramHash(String),
+    #[error("The bootloader program does not match any known bootloader version, so its supported program hashes must be given with --bootloader_config")]
+    UnknownBootloaderProgram,
+    #[error("The simple bootloader program hash {actual} does not match the hash {expected} used with bootloader version {version}")]
+    SimpleBootloaderProgramHashMismatch {
+        version: &'static str,
+        expected: String,
+        actual: String,
+    },
+    #[error(
+        "The Cairo verifier program hash {hash} is not used with bootloader version {version}"
+    )]
+    CairoVerifierProgramHashMismatch { version: &'static str, hash: String },
+    #[error("The Cairo verifier program hash {hash} used with bootloader version {version} is missing from the bootloader config")]
+    MissingCairoVerifierProgramHash { version: &'static str, hash: String },
+    #[error("The bootloader config has no supported Cairo verifier program hashes")]
+    NoCairoVerifierProgramHashes,
+    #[error(
+        "The Cairo verifier program hash {0} is given more than once in the bootloader config"
+    )]
+    DuplicateCairoVerifierProgramHash(String),
+    #[error(transparent)]
+    Target(#[from] crate::target::Error),
+    #[error(transparent)]
+    Manifest(#[from] crate::manifest::Error),
+    #[error("The bootloader output does not follow the expected task output format")]
+    UnexpectedBootloaderOutput,
+    #[error("Expected an output for each of the {tasks} tasks, got {outputs}")]
+    TaskOutputsMismatch { tasks: usize, outputs: usize },
+    #[error("Task {task} output [{output}] does not match its expected output")]
+    UnexpectedTaskOutput { task: String, output: String },
 }
 
 #[allow(clippy::result_large_err)]
@@ -87,35 +161,391 @@ pub fn run_bootloader(
     prove_bootloader_args: &ProveBootloaderArgs,
     tmp_dir: &tempfile::TempDir,
 ) -> Result<CairoBootloaderRunResult, Error> {
-    let bootloader_program = Program::from_bytes(BOOTLOADER_V0_13_1, Some("main"))?;
-    let program_paths = prove_bootloader_args
-        .cairo_programs
-        .as_ref()
-        .map(|paths| paths.iter().map(|p| p.as_path()).collect::<Vec<_>>());
-    let program_inputs = Some(vec![
-        HashMap::new();
-        program_paths.as_ref().map_or(0, |p| p.len())
-    ]);
-
-    let pie_paths = prove_bootloader_args
-        .cairo_pies
-        .as_ref()
-        .map(|paths| paths.iter().map(|p| p.as_path()).collect::<Vec<_>>());
-
-    let tasks = make_bootloader_tasks(
-        program_paths.as_deref(),
-        program_inputs.as_deref(),
-        pie_paths.as_deref(),
+    check_bootloader_target(
+        &prove_bootloader_args.verifier_target,
+        &prove_bootloader_args.layout,
+        &prove_bootloader_args.stone_version,
     )?;
+    // The manifest is validated before any task is run
+    let manifest = prove_bootloader_args
+        .tasks
+        .as_deref()
+        .map(load_task_manifest)
+        .transpose()?;
+    let (bootloader_program, bootloader_config) = load_bootloader(
+        &prove_bootloader_args.bootloader_version,
+        prove_bootloader_args.bootloader_program.as_deref(),
+        prove_bootloader_args.bootloader_config.as_deref(),
+    )?;
+
+    let tasks = match &manifest {
+        Some(manifest) => make_manifest_tasks(manifest, &prove_bootloader_args.layout, tmp_dir)?,
+        None => {
+            let program_paths = prove_bootloader_args
+                .cairo_programs
+                .iter()
+                .flatten()
+                .map(|p| p.as_path())
+                .collect::<Vec<_>>();
+            let program_inputs = vec![HashMap::new(); program_paths.len()];
+            let program_paths = if program_paths.is_empty() {
+                None
+            } else {
+                Some(program_paths)
+            };
+            let program_inputs = Some(program_inputs);
+
+            // Cairo 1 programs are added as PIE tasks, after the given PIEs
+            let cairo1_pie_paths = run_cairo1_programs_to_pies(prove_bootloader_args, tmp_dir)?;
+            let pie_paths = prove_bootloader_args
+                .cairo_pies
+                .iter()
+                .flatten()
+                .chain(cairo1_pie_paths.iter())
+                .map(|p| p.as_path())
+                .collect::<Vec<_>>();
+            let pie_paths = if pie_paths.is_empty() {
+                None
+            } else {
+                Some(pie_paths)
+            };
+
+            make_bootloader_tasks(
+                program_paths.as_deref(),
+                program_inputs.as_deref(),
+                pie_paths.as_deref(),
+            )?
+        }
+    };
+    let n_tasks = tasks.len();
 
     let mut runner = cairo_run_bootloader_in_proof_mode(
         &bootloader_program,
+        bootloader_config,
         tasks,
-        prove_bootloader_args.layout.to_cairo_vm_layout(),
+        &prove_bootloader_args.layout,
         prove_bootloader_args.fact_topologies_output.clone(),
         prove_bootloader_args.ignore_fact_topologies,
+        prove_bootloader_args.single_page,
+        tmp_dir,
+    )?;
+    let run_result = write_to_files(&runner, tmp_dir)?;
+
+    let mut output_buffer = "Bootloader program output:\n".to_string();
+    runner.vm.write_output(&mut output_buffer)?;
+    print!("{output_buffer}");
+
+    let manifest_tasks = manifest.map(|manifest| manifest.tasks).unwrap_or_default();
+    if !manifest_tasks.is_empty() {
+        label_fact_topologies(
+            &prove_bootloader_args.fact_topologies_output,
+            &manifest_tasks,
+        )?;
+    }
+    let check_expected_outputs = manifest_tasks
+        .iter()
+        .any(|task| task.expected_output.is_some());
+    if check_expected_outputs || prove_bootloader_args.task_report_output.is_some() {
+        let task_outputs = read_task_outputs(&mut runner)?;
+        if task_outputs.len() != n_tasks {
+            return Err(Error::TaskOutputsMismatch {
+                tasks: n_tasks,
+                outputs: task_outputs.len(),
+            });
+        }
+        let reports = task_outputs
+            .into_iter()
+            .enumerate()
+            .map(|(i, task_output)| {
+                let manifest_task = manifest_tasks.get(i);
+                TaskReport {
+                    label: manifest_task.and_then(|task| task.label.clone()),
+                    program_hash: task_output.program_hash.to_hex_string(),
+                    expected_output_matches: manifest_task
+                        .and_then(|task| task.expected_output_felts())
+                        .map(|expected| expected == task_output.output),
+                    output: task_output
+                        .output
+                        .iter()
+                        .map(|felt| felt.to_hex_string())
+                        .collect(),
+                }
+            })
+            .collect::<Vec<_>>();
+        if let Some(task_report_output) = &prove_bootloader_args.task_report_output {
+            write_json_to_file(&reports, task_report_output)?;
+        }
+        if let Some((i, report)) = reports
+            .iter()
+            .enumerate()
+            .find(|(_, report)| report.expected_output_matches == Some(false))
+        {
+            return Err(Error::UnexpectedTaskOutput {
+                task: report.label.clone().unwrap_or_else(|| i.to_string()),
+                output: report.output.join(", "),
+            });
+        }
+    }
+
+    Ok(run_result)
+}
+
+/// Builds the bootloader tasks of a task manifest, in the order of the manifest
+///
+/// # Arguments
+///
+/// * `manifest` - A validated task manifest
+/// * `layout` - The layout used to run the Cairo 1 programs to Cairo PIEs
+/// * `tmp_dir` - A temporary directory where the Cairo PIEs of Cairo 1 programs are written
+///
+/// # Returns
+///
+/// A `Result` containing a task for each entry of the manifest, or an `Error` on failure
+#[allow(clippy::result_large_err)]
+fn make_manifest_tasks(
+    manifest: &TaskManifest,
+    layout: &LayoutName,
+    tmp_dir: &tempfile::TempDir,
+) -> Result<Vec<TaskSpec>, Error> {
+    let mut tasks = Vec::with_capacity(manifest.tasks.len());
+    for (i, task) in manifest.tasks.iter().enumerate() {
+        let task_specs = match task.kind {
+            TaskKind::Program => {
+                let program_input: HashMap<String, serde_json::Value> = match &task.input_file {
+                    Some(input_file) => {
+                        serde_json::from_str(&std::fs::read_to_string(input_file)?)?
+                    }
+                    None => HashMap::new(),
+                };
+                make_bootloader_tasks(
+                    Some(&[task.path.as_path()][..]),
+                    Some(&[program_input][..]),
+                    None,
+                )?
+            }
+            TaskKind::Pie => make_bootloader_tasks(None, None, Some(&[task.path.as_path()][..]))?,
+            TaskKind::Cairo1 => {
+                let cairo_pie_path = tmp_dir.path().join(format!("manifest_task_{}_pie.zip", i));
+                run_cairo1_program_to_pie(
+                    &task.path,
+                    task.input_file.as_deref(),
+                    layout,
+                    &cairo_pie_path,
+                    tmp_dir,
+                )?;
+                make_bootloader_tasks(None, None, Some(&[cairo_pie_path.as_path()][..]))?
+            }
+        };
+        tasks.extend(task_specs);
+    }
+    Ok(tasks)
+}
+
+/// Runs the Cairo 1 programs of a bootloader job in non-proof mode and writes their Cairo PIEs
+///
+/// # Arguments
+///
+/// * `prove_bootloader_args` - Arguments for proving bootloader
+/// * `tmp_dir` - A temporary directory where the Cairo PIEs are written
+///
+/// # Returns
+///
+/// A `Result` containing the paths to the Cairo PIEs, in the same order as the programs
+#[allow(clippy::result_large_err)]
+fn run_cairo1_programs_to_pies(
+    prove_bootloader_args: &ProveBootloaderArgs,
+    tmp_dir: &tempfile::TempDir,
+) -> Result<Vec<PathBuf>, Error> {
+    let Some(cairo1_programs) = &prove_bootloader_args.cairo1_programs else {
+        return Ok(vec![]);
+    };
+    let input_files = match &prove_bootloader_args.cairo1_program_input_files {
+        Some(input_files) if input_files.len() != cairo1_programs.len() => {
+            return Err(Error::Cairo1ProgramInputFilesMismatch {
+                programs: cairo1_programs.len(),
+                input_files: input_files.len(),
+            });
+        }
+        Some(input_files) => input_files
+            .iter()
+            .map(|p| Some(p.as_path()))
+            .collect::<Vec<_>>(),
+        None => vec![None; cairo1_programs.len()],
+    };
+
+    cairo1_programs
+        .iter()
+        .zip(input_files)
+        .enumerate()
+        .map(|(i, (program, input_file))| {
+            let cairo_pie_path = tmp_dir.path().join(format!("cairo1_task_{}_pie.zip", i));
+            run_cairo1_program_to_pie(
+                program,
+                input_file,
+                &prove_bootloader_args.layout,
+                &cairo_pie_path,
+                tmp_dir,
+            )?;
+            Ok(cairo_pie_path)
+        })
+        .collect()
+}
+
+#[allow(clippy::result_large_err)]
+fn run_cairo1_program_to_pie(
+    program: &Path,
+    input_file: Option<&Path>,
+    layout: &LayoutName,
+    cairo_pie_path: &Path,
+    tmp_dir: &tempfile::TempDir,
+) -> Result<(), Error> {
+    let args = match input_file {
+        Some(input_file) => {
+            let file_content = std::fs::read_to_string(input_file)?;
+            process_args(&file_content)
+                .map_err(|e| Error::Cairo1Program(program.to_path_buf(), anyhow::anyhow!(e)))?
+        }
+        None => FuncArgs::default(),
+    };
+    run_cairo1_program(program, &args, layout, tmp_dir, false)
+        .and_then(|runner| write_cairo_pie(&runner, cairo_pie_path))
+        .map_err(|e| Error::Cairo1Program(program.to_path_buf(), e))
+}
+
+/// Output of a task, as written by the bootloader
+struct TaskOutput {
+    program_hash: Felt252,
+    output: Vec<Felt252>,
+}
+
+/// Entry of the report written with `--task_report_output`
+#[derive(Debug, Serialize)]
+pub struct TaskReport {
+    pub label: Option<String>,
+    pub program_hash: String,
+    pub output: Vec<String>,
+    pub expected_output_matches: Option<bool>,
+}
+
+/// Reads the output of the bootloader and splits it into the outputs of its tasks
+#[allow(clippy::result_large_err)]
+fn read_task_outputs(runner: &mut CairoRunner) -> Result<Vec<TaskOutput>, Error> {
+    let output_base = runner.vm.get_output_builtin_mut()?.base();
+    let output_size = runner.vm.segments.compute_effective_sizes()[output_base];
+    let output = runner
+        .vm
+        .get_integer_range(Relocatable::from((output_base as isize, 0)), output_size)
+        .map_err(VirtualMachineError::from)?
+        .into_iter()
+        .map(|felt| felt.into_owned())
+        .collect::<Vec<_>>();
+    split_task_outputs(&output)
+}
+
+/// Splits the output of the bootloader into the outputs of its tasks
+///
+/// The bootloader output starts with its config (the simple bootloader program hash and the
+/// hash of the supported Cairo verifier program hashes). It is followed by the number of tasks
+/// and, for each task, the size of its output including this header, its program hash and its
+/// output.
+#[allow(clippy::result_large_err)]
+fn split_task_outputs(output: &[Felt252]) -> Result<Vec<TaskOutput>, Error> {
+    // skip the bootloader config
+    let mut offset = 2;
+    let n_tasks = output
+        .get(offset)
+        .and_then(|n| n.to_usize())
+        .ok_or(Error::UnexpectedBootloaderOutput)?;
+    offset += 1;
+    let mut task_outputs = Vec::with_capacity(n_tasks);
+    for _ in 0..n_tasks {
+        let task_size = output
+            .get(offset)
+            .and_then(|size| size.to_usize())
+            .filter(|size| *size >= 2 && offset + size <= output.len())
+            .ok_or(Error::UnexpectedBootloaderOutput)?;
+        task_outputs.push(TaskOutput {
+            program_hash: output[offset + 1],
+            output: output[offset + 2..offset + task_size].to_vec(),
+        });
+        offset += task_size;
+    }
+    Ok(task_outputs)
+}
+
+/// Adds the labels of the manifest tasks to the fact topologies file
+#[allow(clippy::result_large_err)]
+fn label_fact_topologies(fact_topologies_path: &Path, tasks: &[TaskEntry]) -> Result<(), Error> {
+    if !fact_topologies_path.exists() {
+        return Ok(());
+    }
+    let mut fact_topologies: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(fact_topologies_path)?)?;
+    let Some(entries) = fact_topologies["fact_topologies"].as_array_mut() else {
+        return Ok(());
+    };
+    for (entry, task) in entries.iter_mut().zip(tasks) {
+        if let (Some(entry), Some(label)) = (entry.as_object_mut(), &task.label) {
+            entry.insert("label".to_string(), label.clone().into());
+        }
+    }
+    write_json_to_file(&fact_topologies, fact_topologies_path)?;
+    Ok(())
+}
+
+/// Runs a Cairo PIE in proof mode by executing it as the single task of the bootloader
+///
+/// A Cairo PIE does not contain the hints needed to be re-executed in proof mode on its own, so
+/// the built-in v0.13.1 bootloader loads and re-runs it instead. The resulting trace is the one
+/// of the bootloader: the proof attests to the bootloader program, whose output holds the
+/// program hash and the output of the PIE as its single task, and not to the program of the PIE.
+/// The layout must therefore support the builtins of the bootloader, which `run_cairo` checks
+/// before calling this function. No fact topologies are written, as the proof is not meant to be
+/// split for the GPS verifier.
+///
+/// # Arguments
+///
+/// * `cairo_pie` - Path to the Cairo PIE zip file
+/// * `layout` - The layout used to run the bootloader
+/// * `tmp_dir` - A temporary directory to store intermediate files
+///
+/// # Returns
+///
+/// A `Result` containing `CairoBootloaderRunResult` on success, or an `Error` on failure
+#[allow(clippy::result_large_err)]
+pub fn run_cairo_pie(
+    cairo_pie: &Path,
+    layout: &LayoutName,
+    tmp_dir: &tempfile::TempDir,
+) -> Result<CairoBootloaderRunResult, Error> {
+    let (bootloader_program, bootloader_config) =
+        load_bootloader(&BootloaderVersion::v0_13_1, None, None)?;
+    let tasks = make_bootloader_tasks(None, None, Some(&[cairo_pie][..]))?;
+
+    let mut runner = cairo_run_bootloader_in_proof_mode(
+        &bootloader_program,
+        bootloader_config,
+        tasks,
+        layout,
+        tmp_dir.path().join("fact_topologies.json"),
+        true,
+        false,
+        tmp_dir,
     )?;
+    let run_result = write_to_files(&runner, tmp_dir)?;
+
+    let mut output_buffer = "Cairo PIE output (through the bootloader):\n".to_string();
+    runner.vm.write_output(&mut output_buffer)?;
+    print!("{output_buffer}");
+
+    Ok(run_result)
+}
 
+#[allow(clippy::result_large_err)]
+fn write_to_files(
+    runner: &CairoRunner,
+    tmp_dir: &tempfile::TempDir,
+) -> Result<CairoBootloaderRunResult, Error> {
     let relocated_trace = runner
         .relocated_trace
         .as_ref()
@@ -160,10 +590,6 @@ pub fn run_bootloader(
         .map_err(PublicInputError::Serde)?;
     std::fs::write(air_private_input_path.clone(), air_private_input)?;
 
-    let mut output_buffer = "Bootloader program output:\n".to_string();
-    runner.vm.write_output(&mut output_buffer)?;
-    print!("{output_buffer}");
-
     Ok(CairoBootloaderRunResult {
         air_public_input: air_public_input_path,
         air_private_input: air_private_input_path,
@@ -172,46 +598,250 @@ pub fn run_bootloader(
     })
 }
 
+/// Loads the bootloader program and the program hashes it is run with
+///
+/// The program defaults to the built-in bootloader of `bootloader_version` and the hashes to
+/// the ones that version is deployed with. When a custom program is given, it is identified
+/// against the built-in bootloaders by its program hash. If it matches one of them, the simple
+/// bootloader and Cairo verifier program hashes must match the ones of that version. Otherwise,
+/// the hashes must be provided explicitly through a config file, and if its simple bootloader
+/// program hash is the one of a known version, the Cairo verifier program hashes must be the
+/// ones of that version as well.
+///
+/// # Arguments
+///
+/// * `bootloader_version` - The built-in bootloader version to use by default
+/// * `bootloader_program` - Optional path to a compiled bootloader program
+/// * `bootloader_config` - Optional path to a `BootloaderHashesConfig` JSON file
+///
+/// # Returns
+///
+/// A `Result` containing the bootloader program and its `BootloaderConfig`, or an `Error` on failure
 #[allow(clippy::result_large_err)]
+pub fn load_bootloader(
+    bootloader_version: &BootloaderVersion,
+    bootloader_program: Option<&Path>,
+    bootloader_config: Option<&Path>,
+) -> Result<(Program, BootloaderConfig), Error> {
+    let selected = KNOWN_BOOTLOADERS
+        .iter()
+        .find(|known| &known.version == bootloader_version)
+        .expect("every bootloader version has a built-in program");
+
+    let (program, matching) = match bootloader_program {
+        Some(path) => {
+            let program = Program::from_file(path, Some("main"))?;
+            let program_hash = bootloader_program_hash(&program)?;
+            let mut matching = None;
+            for known in KNOWN_BOOTLOADERS {
+                let known_program = Program::from_bytes(known.program, Some("main"))?;
+                if bootloader_program_hash(&known_program)? == program_hash {
+                    matching = Some(known);
+                    break;
+                }
+            }
+            (program, matching)
+        }
+        None => (
+            Program::from_bytes(selected.program, Some("main"))?,
+            Some(selected),
+        ),
+    };
+
+    let hashes = match bootloader_config {
+        Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+        None => BootloaderHashesConfig {
+            simple_bootloader_program_hash: selected.simple_bootloader_program_hash.to_string(),
+            supported_cairo_verifier_program_hashes: selected
+                .cairo_verifier_program_hashes
+                .iter()
+                .map(|h| h.to_string())
+                .collect(),
+        },
+    };
+    let simple_bootloader_program_hash =
+        parse_program_hash(&hashes.simple_bootloader_program_hash)?;
+    let supported_cairo_verifier_program_hashes = hashes
+        .supported_cairo_verifier_program_hashes
+        .iter()
+        .map(|h| parse_program_hash(h))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if supported_cairo_verifier_program_hashes.is_empty() {
+        return Err(Error::NoCairoVerifierProgramHashes);
+    }
+    for (i, hash) in supported_cairo_verifier_program_hashes.iter().enumerate() {
+        if supported_cairo_verifier_program_hashes[..i].contains(hash) {
+            return Err(Error::DuplicateCairoVerifierProgramHash(
+                hash.to_hex_string(),
+            ));
+        }
+    }
+
+    match matching {
+        Some(known) => {
+            let expected = parse_program_hash(known.simple_bootloader_program_hash)?;
+            if expected != simple_bootloader_program_hash {
+                return Err(Error::SimpleBootloaderProgramHashMismatch {
+                    version: known.version.clone().to_str(),
+                    expected: expected.to_hex_string(),
+                    actual: simple_bootloader_program_hash.to_hex_string(),
+                });
+            }
+            check_cairo_verifier_program_hashes(known, &supported_cairo_verifier_program_hashes)?;
+        }
+        None if bootloader_config.is_none() => return Err(Error::UnknownBootloaderProgram),
+        None => {
+            // a custom bootloader deployed with the simple bootloader of a known version is
+            // deployed with the Cairo verifiers of that version as well
+            for known in KNOWN_BOOTLOADERS {
+                if parse_program_hash(known.simple_bootloader_program_hash)?
+                    == simple_bootloader_program_hash
+                {
+                    check_cairo_verifier_program_hashes(
+                        known,
+                        &supported_cairo_verifier_program_hashes,
+                    )?;
+                }
+            }
+        }
+    }
+
+    Ok((
+        program,
+        BootloaderConfig {
+            simple_bootloader_program_hash,
+            supported_cairo_verifier_program_hashes,
+        },
+    ))
+}
+
+/// Checks that the supported Cairo verifier program hashes are the ones a known bootloader
+/// version is deployed with, regardless of their order
+#[allow(clippy::result_large_err)]
+fn check_cairo_verifier_program_hashes(
+    known: &KnownBootloader,
+    hashes: &[Felt252],
+) -> Result<(), Error> {
+    let expected = known
+        .cairo_verifier_program_hashes
+        .iter()
+        .map(|h| parse_program_hash(h))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(hash) = hashes.iter().find(|hash| !expected.contains(hash)) {
+        return Err(Error::CairoVerifierProgramHashMismatch {
+            version: known.version.clone().to_str(),
+            hash: hash.to_hex_string(),
+        });
+    }
+    if let Some(hash) = expected.iter().find(|hash| !hashes.contains(hash)) {
+        return Err(Error::MissingCairoVerifierProgramHash {
+            version: known.version.clone().to_str(),
+            hash: hash.to_hex_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Finds the built-in bootloader whose bytecode is the program of a proof
+///
+/// # Arguments
+///
+/// * `public_memory` - The public memory of the proof, which contains the program bytecode on page 0
+/// * `program_base` - The address of the first instruction of the program
+///
+/// # Returns
+///
+/// A `Result` containing the version and program hash of the matching bootloader, or `None` if no built-in bootloader matches
+#[allow(clippy::result_large_err)]
+pub fn identify_bootloader(
+    public_memory: &[PublicMemoryEntry],
+    program_base: usize,
+) -> Result<Option<(BootloaderVersion, Felt252)>, Error> {
+    let memory: HashMap<usize, Felt252> = public_memory
+        .iter()
+        .filter(|entry| entry.page == 0)
+        .filter_map(|entry| entry.value.map(|value| (entry.address, value)))
+        .collect();
+    for known in KNOWN_BOOTLOADERS {
+        let program = Program::from_bytes(known.program, Some("main"))?;
+        let matches = program.iter_data().enumerate().all(|(offset, value)| {
+            value
+                .get_int()
+                .is_some_and(|value| memory.get(&(program_base + offset)) == Some(&value))
+        });
+        if matches {
+            return Ok(Some((
+                known.version.clone(),
+                bootloader_program_hash(&program)?,
+            )));
+        }
+    }
+    Ok(None)
+}
+
+#[allow(clippy::result_large_err)]
+fn bootloader_program_hash(program: &Program) -> Result<Felt252, Error> {
+    Ok(compute_program_hash_chain(
+        &program.get_stripped_program()?,
+        0,
+    )?)
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_program_hash(hash: &str) -> Result<Felt252, Error> {
+    parse_felt(hash).ok_or_else(|| Error::InvalidProgramHash(hash.to_string()))
+}
+
+/// Runs the bootloader in proof mode with the given tasks
+///
+/// All tasks are plain tasks: composite tasks, whose output is the output of an inner
+/// bootloader, need a Cairo verifier program, which is not bundled.
+#[allow(clippy::result_large_err, clippy::too_many_arguments)]
 fn cairo_run_bootloader_in_proof_mode(
     bootloader_program: &Program,
+    bootloader_config: BootloaderConfig,
     tasks: Vec<TaskSpec>,
-    layout: LayoutName,
+    layout: &LayoutName,
     fact_topologies_path: PathBuf,
     ignore_fact_topologies: bool,
-) -> Result<CairoRunner, CairoRunError> {
+    single_page: bool,
+    tmp_dir: &tempfile::TempDir,
+) -> Result<CairoRunner, Error> {
     let mut hint_processor = BootloaderHintProcessor::new();
 
+    let dynamic_layout_params = match layout {
+        LayoutName::dynamic => {
+            let cairo_layout_params_file = tmp_dir.path().join("cairo_layout_params_file.json");
+            std::fs::write(cairo_layout_params_file.clone(), DYNAMIC_LAYOUT)?;
+            Some(CairoLayoutParams::from_file(
+                cairo_layout_params_file.as_path(),
+            )?)
+        }
+        _ => None,
+    };
+
     let cairo_run_config = CairoRunConfig {
         entrypoint: "main",
         trace_enabled: true,
         relocate_mem: true,
-        layout,
+        layout: layout.to_cairo_vm_layout(),
         proof_mode: true,
         secure_run: None,
         disable_trace_padding: false,
         allow_missing_builtins: None,
-        dynamic_layout_params: None,
+        dynamic_layout_params,
     };
 
-    let program_hash = Felt252::from_dec_str(SIMPLE_BOOTLOADER_PROGRAM_HASH).unwrap();
-    let verifier_hashes = CAIRO_VERIFIER_PROGRAM_HASHES
-        .iter()
-        .map(|h| Felt252::from_hex(h).unwrap())
-        .collect();
-
     let n_tasks = tasks.len();
 
     let bootloader_input = BootloaderInput {
         simple_bootloader_input: SimpleBootloaderInput {
             fact_topologies_path: Some(fact_topologies_path),
-            single_page: false,
+            single_page,
             tasks,
         },
-        bootloader_config: BootloaderConfig {
-            simple_bootloader_program_hash: program_hash,
-            supported_cairo_verifier_program_hashes: verifier_hashes,
-        },
+        bootloader_config,
         packed_outputs: vec![PackedOutput::Plain(vec![]); n_tasks],
         ignore_fact_topologies,
     };
@@ -219,10 +849,41 @@ fn cairo_run_bootloader_in_proof_mode(
     let mut exec_scopes = ExecutionScopes::new();
     insert_bootloader_input(&mut exec_scopes, bootloader_input);
 
-    cairo_run_program_with_initial_scope(
+    let runner = cairo_run_program_with_initial_scope(
         bootloader_program,
         &cairo_run_config,
         &mut hint_processor,
         exec_scopes,
-    )
+    )?;
+    Ok(runner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn felts(values: &[u64]) -> Vec<Felt252> {
+        values.iter().map(|value| Felt252::from(*value)).collect()
+    }
+
+    #[test]
+    fn test_split_task_outputs() {
+        let task_outputs = split_task_outputs(&felts(&[100, 200, 2, 3, 10, 7, 2, 11])).unwrap();
+        assert_eq!(task_outputs.len(), 2);
+        assert_eq!(task_outputs[0].program_hash, Felt252::from(10));
+        assert_eq!(task_outputs[0].output, felts(&[7]));
+        assert_eq!(task_outputs[1].program_hash, Felt252::from(11));
+        assert!(task_outputs[1].output.is_empty());
+    }
+
+    #[rstest]
+    #[case(vec![])]
+    #[case(vec![100, 200])]
+    #[case(vec![100, 200, 1, 1, 10])]
+    #[case(vec![100, 200, 1, 4, 10, 7])]
+    fn test_split_task_outputs_unexpected_output(#[case] output: Vec<u64>) {
+        let result = split_task_outputs(&felts(&output));
+        assert!(matches!(result, Err(Error::UnexpectedBootloaderOutput)));
+    }
 }
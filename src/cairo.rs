@@ -1,6 +1,8 @@
 use crate::args::{CairoVersion, LayoutName, ProveArgs};
+use crate::bootloader::run_cairo_pie;
 use crate::path_corelib;
-use crate::utils::{get_formatted_air_public_input, process_args, FileWriter};
+use crate::utils::{get_formatted_air_public_input, process_args, FileWriter, FuncArgs};
+use cairo1_run::error::Error as Cairo1RunError;
 use cairo1_run::{cairo_run_program as cairo_run_program_cairo1, Cairo1RunConfig, CairoRunner};
 use cairo_lang_compiler::db::RootDatabase;
 use cairo_lang_compiler::project::setup_project;
@@ -20,14 +22,16 @@ use cairo_vm::types::program::Program;
 use cairo_vm::vm::errors::cairo_run_errors::CairoRunError;
 use cairo_vm::vm::errors::trace_errors::TraceError;
 use cairo_vm::Felt252;
+use itertools::Itertools;
+use num_traits::ToPrimitive;
 use std::collections::HashMap;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use thiserror::Error;
 
 // TODO: get the correct one
-const DYNAMIC_LAYOUT: &str = r#"{
+pub(crate) const DYNAMIC_LAYOUT: &str = r#"{
     "rc_units": 16,
     "memory_units_per_step": 8,
     "public_memory_fraction": 4,
@@ -58,6 +62,21 @@ const DYNAMIC_LAYOUT: &str = r#"{
     "mul_mod_ratio_den": 1
 }"#;
 
+// Layouts that have the builtins the bootloader needs to run a Cairo PIE in proof mode
+const CAIRO_PIE_LAYOUTS: &[LayoutName] = &[
+    LayoutName::starknet,
+    LayoutName::starknet_with_keccak,
+    LayoutName::all_cairo,
+    LayoutName::dynamic,
+];
+
+// Marker placed in front of a `ByteArray` serialized as panic data
+// Value is taken from the Cairo corelib:
+//     https://github.com/starkware-libs/cairo/blob/main/corelib/src/byte_array.cairo
+const BYTE_ARRAY_MAGIC: &str = "0x46a6158a16a947e5916b2a2ca68501a45e93d7110e81aa2d6438b1c57c879a3";
+// Number of bytes stored in each full word of a `ByteArray`
+const BYTES_IN_BYTES31: usize = 31;
+
 #[derive(Debug)]
 pub struct CairoRunResult {
     pub air_public_input: PathBuf,
@@ -82,34 +101,108 @@ pub enum Error {
     Program(#[from] ProgramError),
     #[error(transparent)]
     ProgramInput(#[from] serde_json::Error),
+    #[error("Either a Cairo program or a Cairo PIE should be specified")]
+    CairoProgramNotSpecified,
+    #[error("The automatic layout is not supported for Cairo PIEs")]
+    AutomaticLayoutWithCairoPie,
+    #[error(
+        "Cairo PIEs are proved through the bootloader, which cannot run with the {0} layout. Supported layouts: {}",
+        CAIRO_PIE_LAYOUTS.iter().map(|layout| layout.clone().to_str()).join(", ")
+    )]
+    UnsupportedCairoPieLayout(&'static str),
+    #[error("Cairo 1 program panicked with [{}]: {message}", format_panic_felts(.felts))]
+    Panic {
+        felts: Vec<Felt252>,
+        message: String,
+    },
 }
 
 pub fn run_cairo(
     args: &ProveArgs,
     tmp_dir: &tempfile::TempDir,
 ) -> Result<CairoRunResult, anyhow::Error> {
-    let filename = args.cairo_program.file_stem().unwrap().to_str().unwrap();
+    if let Some(cairo_pie) = &args.cairo_pie {
+        if args.layout == LayoutName::automatic {
+            return Err(Error::AutomaticLayoutWithCairoPie.into());
+        }
+        if !CAIRO_PIE_LAYOUTS.contains(&args.layout) {
+            return Err(Error::UnsupportedCairoPieLayout(args.layout.clone().to_str()).into());
+        }
+        let result = run_cairo_pie(cairo_pie, &args.layout, tmp_dir)
+            .map_err(|e| anyhow::anyhow!("Failed to run Cairo PIE: {}", e))?;
+        return Ok(CairoRunResult {
+            air_public_input: result.air_public_input,
+            air_private_input: result.air_private_input,
+            memory_file: result.memory_file,
+            trace_file: result.trace_file,
+        });
+    }
+
+    let cairo_program = args
+        .cairo_program
+        .as_ref()
+        .ok_or(Error::CairoProgramNotSpecified)?;
+    let filename = cairo_program.file_stem().unwrap().to_str().unwrap();
 
     match args.cairo_version {
         CairoVersion::cairo0 => {
-            let runner = run_cairo0(args, tmp_dir)?;
+            let runner = run_cairo0(args, tmp_dir, true)?;
             let file_paths = write_to_files(&runner, tmp_dir, filename)?;
             Ok(file_paths)
         }
         CairoVersion::cairo1 => {
-            let runner = run_cairo1(args, tmp_dir)?;
+            let runner = run_cairo1(args, tmp_dir, true)?;
             let file_paths = write_to_files(&runner, tmp_dir, filename)?;
             Ok(file_paths)
         }
     }
 }
 
+/// Runs a Cairo 0 or Cairo 1 program in non-proof mode and writes its Cairo PIE
+///
+/// The resulting PIE can later be proved as a task of the bootloader, e.g. with
+/// `prove-bootloader --cairo_pies`.
+///
+/// # Arguments
+///
+/// * `args` - The arguments for the prove command
+/// * `cairo_pie_output` - Path of the Cairo PIE zip file to write
+/// * `tmp_dir` - A temporary directory to store intermediate files
+///
+/// # Returns
+///
+/// An empty `Result` on success, or an `anyhow::Error` on failure
+pub fn run_cairo_to_pie(
+    args: &ProveArgs,
+    cairo_pie_output: &Path,
+    tmp_dir: &tempfile::TempDir,
+) -> Result<(), anyhow::Error> {
+    let runner = match args.cairo_version {
+        CairoVersion::cairo0 => run_cairo0(args, tmp_dir, false)?,
+        CairoVersion::cairo1 => run_cairo1(args, tmp_dir, false)?,
+    };
+    write_cairo_pie(&runner, cairo_pie_output)
+}
+
+/// Writes the Cairo PIE of a runner that was run in non-proof mode
+pub(crate) fn write_cairo_pie(
+    runner: &CairoRunner,
+    cairo_pie_output: &Path,
+) -> Result<(), anyhow::Error> {
+    // the bootloader expects all extra segments of a task to be merged into one
+    runner
+        .get_cairo_pie()?
+        .write_zip_file(cairo_pie_output, true)?;
+    Ok(())
+}
+
 /// Runs a Cairo 0 program and generates the necessary outputs for proving
 ///
 /// # Arguments
 ///
 /// * `prove_args` - The arguments for the prove command
 /// * `tmp_dir` - A temporary directory to store intermediate files
+/// * `proof_mode` - Whether to run in proof mode, or in non-proof mode to create a Cairo PIE
 ///
 /// # Returns
 ///
@@ -122,8 +215,13 @@ pub fn run_cairo(
 pub fn run_cairo0(
     prove_args: &ProveArgs,
     tmp_dir: &tempfile::TempDir,
+    proof_mode: bool,
 ) -> Result<CairoRunner, anyhow::Error> {
-    let program = Program::from_file(&prove_args.cairo_program, Some("main"))?;
+    let cairo_program = prove_args
+        .cairo_program
+        .as_ref()
+        .ok_or(Error::CairoProgramNotSpecified)?;
+    let program = Program::from_file(cairo_program, Some("main"))?;
     let program_input = if let Some(program_input_file) = prove_args.program_input_file.clone() {
         let program_input_file_str = std::fs::read_to_string(program_input_file)?;
         serde_json::from_str::<HashMap<String, serde_json::Value>>(&program_input_file_str)?
@@ -180,10 +278,10 @@ pub fn run_cairo0(
             std::fs::write(cairo_layout_params_file.clone(), DYNAMIC_LAYOUT)?;
             CairoRunConfig {
                 entrypoint: "main",
-                trace_enabled: true,
-                relocate_mem: true,
+                trace_enabled: proof_mode,
+                relocate_mem: proof_mode,
                 layout: cairo_vm::types::layout_name::LayoutName::dynamic,
-                proof_mode: true,
+                proof_mode,
                 secure_run: None,
                 disable_trace_padding: false,
                 allow_missing_builtins: None,
@@ -194,10 +292,10 @@ pub fn run_cairo0(
         }
         layout => CairoRunConfig {
             entrypoint: "main",
-            trace_enabled: true,
-            relocate_mem: true,
+            trace_enabled: proof_mode,
+            relocate_mem: proof_mode,
             layout: get_layout(layout),
-            proof_mode: true,
+            proof_mode,
             secure_run: None,
             disable_trace_padding: false,
             allow_missing_builtins: None,
@@ -215,6 +313,7 @@ pub fn run_cairo0(
 ///
 /// * `prove_args` - The arguments for the prove command
 /// * `tmp_dir` - A temporary directory to store intermediate files
+/// * `proof_mode` - Whether to run in proof mode, or in non-proof mode to create a Cairo PIE
 ///
 /// # Returns
 ///
@@ -222,10 +321,11 @@ pub fn run_cairo0(
 ///
 /// # Note
 ///
-/// This function ignores the following arguments to cairo1-run: `append_return_values`, `cairo_pie_output`, `print_output`.
+/// This function ignores the following arguments to cairo1-run: `print_output`.
 pub fn run_cairo1(
     prove_args: &ProveArgs,
     tmp_dir: &tempfile::TempDir,
+    proof_mode: bool,
 ) -> Result<CairoRunner, anyhow::Error> {
     let args = if let Some(program_input_file) = &prove_args.program_input_file {
         let file_content = std::fs::read_to_string(program_input_file)?;
@@ -233,17 +333,50 @@ pub fn run_cairo1(
     } else {
         prove_args.program_input.clone()
     };
+    let cairo_program = prove_args
+        .cairo_program
+        .as_ref()
+        .ok_or(Error::CairoProgramNotSpecified)?;
 
-    let cairo_run_config = match &prove_args.layout {
+    run_cairo1_program(
+        cairo_program,
+        &args,
+        &prove_args.layout,
+        tmp_dir,
+        proof_mode,
+    )
+}
+
+/// Compiles (if needed) and runs a Cairo 1 source or Sierra file with the given arguments
+///
+/// # Arguments
+///
+/// * `cairo_program` - Path to the Cairo 1 source file or the Sierra file
+/// * `args` - The arguments passed to the `main` function of the program
+/// * `layout` - The layout used to run the program
+/// * `tmp_dir` - A temporary directory to store intermediate files
+/// * `proof_mode` - Whether to run in proof mode, or in non-proof mode to create a Cairo PIE
+///
+/// # Returns
+///
+/// A `Result` containing the `CairoRunner` on success, or an `anyhow::Error` on failure
+pub fn run_cairo1_program(
+    cairo_program: &Path,
+    args: &FuncArgs,
+    layout: &LayoutName,
+    tmp_dir: &tempfile::TempDir,
+    proof_mode: bool,
+) -> Result<CairoRunner, anyhow::Error> {
+    let cairo_run_config = match layout {
         LayoutName::dynamic | LayoutName::automatic => {
             let cairo_layout_params_file = tmp_dir.path().join("cairo_layout_params_file.json");
             std::fs::write(cairo_layout_params_file.clone(), DYNAMIC_LAYOUT)?;
             Cairo1RunConfig {
-                proof_mode: true,
+                proof_mode,
                 serialize_output: true,
-                relocate_mem: true,
+                relocate_mem: proof_mode,
                 layout: cairo_vm::types::layout_name::LayoutName::dynamic,
-                trace_enabled: true,
+                trace_enabled: proof_mode,
                 args: &args.0,
                 finalize_builtins: true,
                 append_return_values: true,
@@ -253,11 +386,11 @@ pub fn run_cairo1(
             }
         }
         layout => Cairo1RunConfig {
-            proof_mode: true,
+            proof_mode,
             serialize_output: true,
-            relocate_mem: true,
+            relocate_mem: proof_mode,
             layout: layout.to_cairo_vm_layout(),
-            trace_enabled: true,
+            trace_enabled: proof_mode,
             args: &args.0,
             finalize_builtins: true,
             append_return_values: true,
@@ -266,7 +399,7 @@ pub fn run_cairo1(
     };
 
     // Try to parse the file as a sierra program
-    let file = std::fs::read(&prove_args.cairo_program)?;
+    let file = std::fs::read(cairo_program)?;
     let sierra_program = match serde_json::from_slice(&file) {
         Ok(program) => program,
         Err(_) => {
@@ -281,7 +414,7 @@ pub fn run_cairo1(
                 .unwrap();
 
             init_dev_corelib(&mut db, path_corelib()?.join("src"));
-            let main_crate_ids = setup_project(&mut db, &prove_args.cairo_program).unwrap();
+            let main_crate_ids = setup_project(&mut db, cairo_program).unwrap();
             let sierra_program_with_dbg =
                 compile_prepared_db(&db, main_crate_ids, compiler_config).unwrap();
             sierra_program_with_dbg.program
@@ -289,7 +422,13 @@ pub fn run_cairo1(
     };
 
     let (runner, _, serialized_output) =
-        cairo_run_program_cairo1(&sierra_program, cairo_run_config)?;
+        cairo_run_program_cairo1(&sierra_program, cairo_run_config).map_err(|e| match e {
+            Cairo1RunError::RunPanic(felts) => {
+                let message = decode_panic_data(&felts);
+                anyhow::Error::from(Error::Panic { felts, message })
+            }
+            e => anyhow::Error::from(e),
+        })?;
     println!("Cairo1 program output: {:?}", serialized_output);
 
     Ok(runner)
@@ -313,14 +452,87 @@ pub fn get_cairo_runner(
     println!("Running Cairo again to get dynamic params...");
 
     let runner = if prove_args.cairo_version == CairoVersion::cairo1 {
-        run_cairo1(prove_args, tmp_dir).unwrap()
+        run_cairo1(prove_args, tmp_dir, true).unwrap()
     } else {
-        run_cairo0(prove_args, tmp_dir).unwrap()
+        run_cairo0(prove_args, tmp_dir, true).unwrap()
     };
 
     Ok(runner)
 }
 
+/// Decodes the panic data of a Cairo 1 program into a human-readable message
+///
+/// Panic data is a list of felts, where each felt is either a Cairo short string
+/// (e.g. `'Index out of bounds'`) or the start of a serialized `ByteArray`, which is
+/// prefixed by `BYTE_ARRAY_MAGIC`. Felts that cannot be decoded are kept as hex values.
+///
+/// # Arguments
+///
+/// * `felts` - The panic data returned by the Cairo 1 runner
+///
+/// # Returns
+///
+/// The decoded messages separated by `, `
+pub fn decode_panic_data(felts: &[Felt252]) -> String {
+    let byte_array_magic = Felt252::from_hex(BYTE_ARRAY_MAGIC).unwrap();
+    let mut messages = vec![];
+    let mut i = 0;
+    while i < felts.len() {
+        if felts[i] == byte_array_magic {
+            if let Some((message, len)) = decode_byte_array(&felts[i + 1..]) {
+                messages.push(message);
+                i += 1 + len;
+                continue;
+            }
+        }
+        messages.push(decode_short_string(&felts[i]).unwrap_or_else(|| felts[i].to_hex_string()));
+        i += 1;
+    }
+    messages.join(", ")
+}
+
+/// Decodes a felt as a Cairo short string, i.e. up to 31 ASCII characters
+fn decode_short_string(felt: &Felt252) -> Option<String> {
+    let bytes = felt.to_bytes_be();
+    let bytes = bytes
+        .iter()
+        .skip_while(|b| **b == 0)
+        .copied()
+        .collect::<Vec<u8>>();
+    if bytes.is_empty() || !bytes.iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+        return None;
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Decodes a serialized `ByteArray` (without the magic prefix)
+///
+/// The serialization is `[n_full_words, full_words..., pending_word, pending_word_len]`.
+/// Returns the decoded string and the number of felts consumed.
+fn decode_byte_array(felts: &[Felt252]) -> Option<(String, usize)> {
+    // the length comes from the panic data, so it is not trusted
+    let n_full_words = felts.first()?.to_usize()?;
+    let full_words = felts.get(1..n_full_words.checked_add(1)?)?;
+    let pending_word = felts.get(n_full_words.checked_add(1)?)?;
+    let pending_word_len = felts.get(n_full_words.checked_add(2)?)?.to_usize()?;
+    if pending_word_len >= BYTES_IN_BYTES31 {
+        return None;
+    }
+
+    let mut bytes = vec![];
+    for word in full_words {
+        bytes.extend_from_slice(&word.to_bytes_be()[32 - BYTES_IN_BYTES31..]);
+    }
+    bytes.extend_from_slice(&pending_word.to_bytes_be()[32 - pending_word_len..]);
+
+    let message = String::from_utf8(bytes).ok()?;
+    Some((message, n_full_words.checked_add(3)?))
+}
+
+fn format_panic_felts(felts: &[Felt252]) -> String {
+    felts.iter().map(|f| f.to_hex_string()).join(", ")
+}
+
 fn get_layout(layout: &LayoutName) -> cairo_vm::types::layout_name::LayoutName {
     match layout {
         LayoutName::dynamic => cairo_vm::types::layout_name::LayoutName::dynamic,
@@ -405,3 +617,64 @@ fn write_to_files(
         trace_file: trace_path,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn short_string(s: &str) -> Felt252 {
+        Felt252::from_bytes_be_slice(s.as_bytes())
+    }
+
+    #[rstest]
+    #[case(vec![short_string("Index out of bounds")], "Index out of bounds")]
+    #[case(
+        vec![short_string("Option::unwrap failed."), short_string("u32_sub Overflow")],
+        "Option::unwrap failed., u32_sub Overflow"
+    )]
+    #[case(vec![Felt252::from(0x1234)], "0x1234")]
+    fn test_decode_panic_data_short_strings(#[case] felts: Vec<Felt252>, #[case] expected: &str) {
+        assert_eq!(decode_panic_data(&felts), expected);
+    }
+
+    #[test]
+    fn test_decode_panic_data_byte_array() {
+        let message = "Invalid input: the array should not be empty!";
+        let (full_words, pending_word) = message.as_bytes().split_at(BYTES_IN_BYTES31);
+        let felts = vec![
+            Felt252::from_hex(BYTE_ARRAY_MAGIC).unwrap(),
+            Felt252::from(1),
+            Felt252::from_bytes_be_slice(full_words),
+            Felt252::from_bytes_be_slice(pending_word),
+            Felt252::from(pending_word.len()),
+            short_string("trailing"),
+        ];
+        assert_eq!(decode_panic_data(&felts), format!("{}, trailing", message));
+    }
+
+    #[test]
+    fn test_decode_panic_data_truncated_byte_array() {
+        let felts = vec![
+            Felt252::from_hex(BYTE_ARRAY_MAGIC).unwrap(),
+            Felt252::from(3),
+        ];
+        assert_eq!(
+            decode_panic_data(&felts),
+            format!("{}, 0x3", BYTE_ARRAY_MAGIC)
+        );
+    }
+
+    #[test]
+    fn test_decode_panic_data_overflowing_byte_array_length() {
+        let felts = vec![
+            Felt252::from_hex(BYTE_ARRAY_MAGIC).unwrap(),
+            Felt252::from(usize::MAX),
+            Felt252::from(1),
+        ];
+        assert_eq!(
+            decode_panic_data(&felts),
+            format!("{}, {:#x}, 0x1", BYTE_ARRAY_MAGIC, usize::MAX)
+        );
+    }
+}
@@ -0,0 +1,534 @@
+use crate::args::{
+    BootloaderVersion, CairoVersion, CheckCompatArgs, LayoutName, MemoryVerification, Preset,
+    SerializationType, StoneVersion, VerifierTarget,
+};
+use crate::bootloader::identify_bootloader;
+use crate::config::{CommitmentScheme, ProofParameters};
+use crate::prover::PublicInput;
+use crate::serialize::monolith_calldata;
+use crate::target::check_layout;
+use crate::verifier::accepts_proof;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use thiserror::Error;
+
+// Commits of the Stone prover releases, see the `--stone_version` argument of `prove`
+const STONE_V5_COMMIT: &str = "7ac17c8ba63a789604350e501558ef0ab990fd88";
+const STONE_V6_COMMIT: &str = "1414a545e4fb38a85391289abe91dd4467d268e1";
+// Version assumed for proofs that do not record their commit, the default of `prove`
+const DEFAULT_STONE_VERSION: StoneVersion = StoneVersion::V6;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    IO(#[from] std::io::Error),
+    #[error("Failed to parse the proof: {0}")]
+    SerdeJson(#[from] serde_json::Error),
+    #[error(transparent)]
+    Bootloader(#[from] crate::bootloader::Error),
+    #[error("The proof was created by Stone {recorded} but --stone_version is {given}")]
+    StoneVersionMismatch {
+        recorded: &'static str,
+        given: &'static str,
+    },
+    #[error("The proof cannot be verified by {target}: {}", .reasons.join(", "))]
+    Incompatible {
+        target: &'static str,
+        reasons: Vec<String>,
+    },
+    #[error("The {memory_verification} memory verification mode is not supported for {cairo_version} programs")]
+    MemoryVerificationMismatch {
+        cairo_version: &'static str,
+        memory_verification: &'static str,
+    },
+}
+
+/// The parts of a Stone proof file that determine where it can be verified
+///
+/// Stone omits the parameters that were not set, e.g. bootloader proofs have no hash settings
+#[derive(Debug, Deserialize)]
+struct ProofFile {
+    #[serde(default)]
+    proof_parameters: ProofParameters,
+    public_input: PublicInput,
+    #[serde(default)]
+    version: Option<ProofVersion>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProofVersion {
+    #[serde(default)]
+    commit_hash: String,
+}
+
+/// Verifier config of Integrity, which selects the verifier a proof is submitted to
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct VerifierConfig {
+    pub layout: String,
+    pub hasher: String,
+    pub stone_version: String,
+    pub memory_verification: String,
+}
+
+impl fmt::Display for VerifierConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}/{}",
+            self.layout, self.hasher, self.stone_version, self.memory_verification
+        )
+    }
+}
+
+/// Whether a proof can be used with a verifier, and why not if it cannot
+#[derive(Debug, Serialize)]
+pub struct TargetCompat {
+    pub target: &'static str,
+    pub compatible: bool,
+    /// Verifier configs accepting the proof, for verifiers with several configs
+    pub verifier_configs: Vec<VerifierConfig>,
+    pub reasons: Vec<String>,
+    pub notes: Vec<String>,
+}
+
+impl TargetCompat {
+    fn new(target: &'static str) -> Self {
+        TargetCompat {
+            target,
+            compatible: false,
+            verifier_configs: vec![],
+            reasons: vec![],
+            notes: vec![],
+        }
+    }
+
+    fn finish(mut self) -> Self {
+        self.compatible = self.reasons.is_empty();
+        self
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompatReport {
+    pub layout: LayoutName,
+    pub stone_version: Option<StoneVersion>,
+    pub bootloader_version: Option<BootloaderVersion>,
+    pub bootloader_program_hash: Option<String>,
+    pub targets: Vec<TargetCompat>,
+}
+
+impl fmt::Display for CompatReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Layout: {}", self.layout)?;
+        match &self.stone_version {
+            Some(version) => writeln!(f, "Stone version: {}", version.clone().to_str())?,
+            None => writeln!(f, "Stone version: unknown")?,
+        }
+        match (&self.bootloader_version, &self.bootloader_program_hash) {
+            (Some(version), Some(hash)) => writeln!(
+                f,
+                "Bootloader: {} (program hash {})",
+                version.clone().to_str(),
+                hash
+            )?,
+            _ => writeln!(f, "Bootloader: none")?,
+        }
+        for target in &self.targets {
+            let status = if target.compatible {
+                "compatible"
+            } else {
+                "not compatible"
+            };
+            writeln!(f, "{}: {}", target.target, status)?;
+            for config in &target.verifier_configs {
+                writeln!(f, "  config: {}", config)?;
+            }
+            for reason in &target.reasons {
+                writeln!(f, "  - {}", reason)?;
+            }
+            for note in &target.notes {
+                writeln!(f, "  note: {}", note)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reports the verifiers a proof can be used with
+///
+/// The decision is based on the layout and hashes of the proof, its Stone version and, for the
+/// Ethereum GPS verifier, whether its program is a known bootloader. The Stone version is found
+/// by `resolve_stone_version`. Stone binaries built without git metadata, such as the ones bundled
+/// with the CLI, record no commit, so the proof is then checked with the bundled v5 and v6
+/// verifiers: v6 also hashes `n_verifier_friendly_commitment_layers` into the public input, so a
+/// valid proof is only accepted by the verifier of its version.
+///
+/// # Arguments
+///
+/// * `args` - Arguments for checking the compatibility of a proof
+///
+/// # Returns
+///
+/// A `Result` containing the `CompatReport`, or an `Error` if the proof cannot be read
+#[allow(clippy::result_large_err)]
+pub fn check_compat(args: &CheckCompatArgs) -> Result<CompatReport, Error> {
+    let input = std::fs::read_to_string(&args.proof)?;
+    let proof: ProofFile = serde_json::from_str(&input)?;
+    let stone_version = resolve_stone_version(&proof, &args.stone_version)?
+        .or_else(|| infer_stone_version(&args.proof));
+
+    let layout = proof.public_input.layout.clone();
+    let scheme = proof.proof_parameters.commitment_scheme();
+    let program_base = proof
+        .public_input
+        .memory_segments
+        .get("program")
+        .map(|segment| segment.begin_addr)
+        .unwrap_or(1);
+    let bootloader = identify_bootloader(&proof.public_input.public_memory, program_base)?;
+
+    let mut targets = vec![
+        check_stone(StoneVersion::V5, &stone_version),
+        check_stone(StoneVersion::V6, &stone_version),
+    ];
+    for serialization_type in [SerializationType::monolith, SerializationType::split] {
+        let mut target =
+            check_integrity(serialization_type.clone(), &layout, &scheme, &stone_version);
+        if serialization_type == SerializationType::monolith && target.reasons.is_empty() {
+            match monolith_calldata(input.clone()) {
+                Ok(calldata) => target.notes.push(format!(
+                    "the calldata has {} felts, which must fit in a single Starknet transaction",
+                    calldata.len()
+                )),
+                Err(e) => target
+                    .reasons
+                    .push(format!("the proof cannot be serialized: {}", e)),
+            }
+        }
+        targets.push(target.finish());
+    }
+    targets.push(check_ethereum(
+        &layout,
+        &scheme,
+        &stone_version,
+        bootloader.is_some(),
+    ));
+
+    Ok(CompatReport {
+        layout,
+        stone_version,
+        bootloader_version: bootloader.as_ref().map(|(version, _)| version.clone()),
+        bootloader_program_hash: bootloader.map(|(_, hash)| hash.to_hex_string()),
+        targets,
+    })
+}
+
+/// Derives the Integrity verifier config of a proof and checks that Integrity accepts it
+///
+/// The proof is not run through the Stone verifiers, so the Stone version is taken from the proof
+/// or `stone_version`, or else assumed to be the default Stone version of `prove`.
+///
+/// # Arguments
+///
+/// * `proof_file` - Path to the proof file
+/// * `serialization_type` - Whether the proof is verified in a single transaction or split
+/// * `stone_version` - The Stone version used to create the proof, if not recorded in the proof.
+///   If it cannot be found, the default Stone version of `prove` is assumed
+/// * `cairo_version` - The Cairo version of the proven program
+/// * `memory_verification` - The memory verification mode, `strict` by default for Cairo 0
+///
+/// # Returns
+///
+/// A `Result` containing the `VerifierConfig`, or an `Error` if the proof is not accepted under
+/// that config
+pub fn integrity_verifier_config(
+    proof_file: &Path,
+    serialization_type: SerializationType,
+    stone_version: &Option<StoneVersion>,
+    cairo_version: &CairoVersion,
+    memory_verification: &Option<MemoryVerification>,
+) -> Result<VerifierConfig, Error> {
+    let memory_verification = match (cairo_version, memory_verification) {
+        (CairoVersion::cairo0, None) => MemoryVerification::strict,
+        (CairoVersion::cairo1, None) => MemoryVerification::cairo1,
+        (
+            CairoVersion::cairo0,
+            Some(mode @ (MemoryVerification::strict | MemoryVerification::relaxed)),
+        )
+        | (CairoVersion::cairo1, Some(mode @ MemoryVerification::cairo1)) => mode.clone(),
+        (cairo_version, Some(mode)) => {
+            return Err(Error::MemoryVerificationMismatch {
+                cairo_version: cairo_version.clone().to_str(),
+                memory_verification: mode.clone().to_str(),
+            })
+        }
+    };
+
+    let proof: ProofFile = serde_json::from_str(&std::fs::read_to_string(proof_file)?)?;
+    let stone_version = match resolve_stone_version(&proof, stone_version)? {
+        Some(stone_version) => stone_version,
+        None => {
+            eprintln!(
+                "Warning: the version of the Stone prover could not be found from the proof, assuming {}. Pass --stone_version if the proof was created by another version",
+                DEFAULT_STONE_VERSION.to_str()
+            );
+            DEFAULT_STONE_VERSION
+        }
+    };
+    let target = check_integrity(
+        serialization_type,
+        &proof.public_input.layout,
+        &proof.proof_parameters.commitment_scheme(),
+        &Some(stone_version),
+    );
+    if !target.reasons.is_empty() {
+        return Err(Error::Incompatible {
+            target: target.target,
+            reasons: target.reasons,
+        });
+    }
+    target
+        .verifier_configs
+        .into_iter()
+        .find(|config| config.memory_verification == memory_verification.clone().to_str())
+        .ok_or(Error::Incompatible {
+            target: target.target,
+            reasons: vec!["no verifier config matches the proof".to_string()],
+        })
+}
+
+/// Finds the Stone version of a proof
+///
+/// The version is read from the Stone commit recorded in the proof, or else taken from `given`.
+///
+/// # Arguments
+///
+/// * `proof` - The parsed proof file
+/// * `given` - The Stone version given by the user
+///
+/// # Returns
+///
+/// A `Result` containing the Stone version, `None` if neither the proof nor the user gives it, or
+/// an `Error` if `given` differs from the recorded version
+fn resolve_stone_version(
+    proof: &ProofFile,
+    given: &Option<StoneVersion>,
+) -> Result<Option<StoneVersion>, Error> {
+    let recorded = proof
+        .version
+        .as_ref()
+        .and_then(|version| stone_version_from_commit(&version.commit_hash));
+    match (recorded, given) {
+        (Some(recorded), Some(given)) if recorded != *given => Err(Error::StoneVersionMismatch {
+            recorded: recorded.to_str(),
+            given: given.clone().to_str(),
+        }),
+        (Some(recorded), _) => Ok(Some(recorded)),
+        (None, Some(given)) => Ok(Some(given.clone())),
+        (None, None) => Ok(None),
+    }
+}
+
+fn infer_stone_version(proof_file: &Path) -> Option<StoneVersion> {
+    let accepted: Vec<&StoneVersion> = StoneVersion::value_variants()
+        .iter()
+        .filter(|version| accepts_proof(proof_file, version))
+        .collect();
+    match accepted.as_slice() {
+        [version] => Some((*version).clone()),
+        _ => None,
+    }
+}
+
+fn stone_version_from_commit(commit_hash: &str) -> Option<StoneVersion> {
+    // short hashes are accepted, but not the placeholder of builds without git metadata
+    let matches = |commit: &str| commit_hash.len() >= 7 && commit.starts_with(commit_hash);
+    if matches(STONE_V5_COMMIT) {
+        Some(StoneVersion::V5)
+    } else if matches(STONE_V6_COMMIT) {
+        Some(StoneVersion::V6)
+    } else {
+        None
+    }
+}
+
+fn check_stone(version: StoneVersion, stone_version: &Option<StoneVersion>) -> TargetCompat {
+    let mut target = TargetCompat::new(match version {
+        StoneVersion::V5 => "stone-v5",
+        StoneVersion::V6 => "stone-v6",
+    });
+    match stone_version {
+        Some(proof_version) if *proof_version != version => target.reasons.push(format!(
+            "the proof was created by Stone {}, whose public input hash differs",
+            proof_version.clone().to_str()
+        )),
+        Some(_) => {}
+        None => target.notes.push(format!(
+            "only if the proof was created by Stone {}",
+            version.to_str()
+        )),
+    }
+    target.finish()
+}
+
+fn check_integrity(
+    serialization_type: SerializationType,
+    layout: &LayoutName,
+    scheme: &CommitmentScheme,
+    stone_version: &Option<StoneVersion>,
+) -> TargetCompat {
+    let mut target = TargetCompat::new(match serialization_type {
+        SerializationType::monolith => "integrity-monolith",
+        SerializationType::split => "integrity-split",
+    });
+    if let Err(e) = check_layout(&VerifierTarget::integrity, layout) {
+        target.reasons.push(e.to_string());
+    } else if serialization_type == SerializationType::monolith && *layout != LayoutName::recursive
+    {
+        target.reasons.push(format!(
+            "monolith serialization only supports the recursive layout, use split serialization for the {} layout",
+            layout
+        ));
+    }
+
+    // Integrity verifiers read the number of verifier friendly layers from the proof
+    let presets: Vec<&Preset> = Preset::value_variants()
+        .iter()
+        .filter(|preset| preset.verifier_target() == VerifierTarget::integrity)
+        .filter(|preset| {
+            let expected = preset.commitment_scheme();
+            *scheme
+                == CommitmentScheme {
+                    n_verifier_friendly_commitment_layers: scheme
+                        .n_verifier_friendly_commitment_layers,
+                    ..expected
+                }
+        })
+        .collect();
+    if presets.is_empty() {
+        target.reasons.push(format!(
+            "the hashes of the proof (channel {}, commitment {}, proof of work {}) do not match any Integrity hasher, which require the poseidon3 channel hash with verifier friendly channel updates and either keccak256_masked160_lsb commitments with keccak256 proof of work or blake256_masked248_lsb commitments with blake256 proof of work",
+            scheme.channel_hash.clone().to_str(),
+            scheme.commitment_hash.clone().to_str(),
+            scheme.pow_hash.clone().to_str()
+        ));
+    }
+    if !target.reasons.is_empty() {
+        return target;
+    }
+
+    for preset in presets {
+        if stone_version
+            .as_ref()
+            .is_some_and(|version| *version != preset.stone_version())
+        {
+            continue;
+        }
+        let integrity_stone_version = match preset.stone_version() {
+            StoneVersion::V5 => "stone5",
+            StoneVersion::V6 => "stone6",
+        };
+        for memory_verification in MemoryVerification::value_variants() {
+            target.verifier_configs.push(VerifierConfig {
+                layout: layout.to_string(),
+                hasher: preset.integrity_hasher().unwrap_or_default().to_string(),
+                stone_version: integrity_stone_version.to_string(),
+                memory_verification: memory_verification.clone().to_str().to_string(),
+            });
+        }
+    }
+    if stone_version.is_none() {
+        target.notes.push(
+            "the Stone version of the proof is unknown, pass --stone_version to select between the stone5 and stone6 configs".to_string(),
+        );
+    }
+    target.notes.push(
+        "the memory verification mode is cairo1 for Cairo 1 programs and strict or relaxed for Cairo 0 programs"
+            .to_string(),
+    );
+    target
+}
+
+fn check_ethereum(
+    layout: &LayoutName,
+    scheme: &CommitmentScheme,
+    stone_version: &Option<StoneVersion>,
+    is_bootloader: bool,
+) -> TargetCompat {
+    let target_name = VerifierTarget::ethereum;
+    let mut target = TargetCompat::new("ethereum");
+    if let Err(e) = check_layout(&target_name, layout) {
+        target.reasons.push(e.to_string());
+    }
+    match stone_version {
+        Some(version) if !target_name.supported_stone_versions().contains(version) => {
+            target.reasons.push(format!(
+                "Stone {} proofs are not accepted by the GPS verifier",
+                version.clone().to_str()
+            ))
+        }
+        Some(_) => {}
+        None => target
+            .notes
+            .push("only if the proof was created by Stone V5".to_string()),
+    }
+    if *scheme != Preset::ethereum.commitment_scheme() {
+        target.reasons.push(
+            "the GPS verifier expects the default hashes of Stone: keccak256 channel and proof of work hashes, keccak256_masked160_msb commitments and no verifier friendly commitment layers or channel updates".to_string(),
+        );
+    }
+    if !is_bootloader {
+        target.reasons.push(
+            "the program of the proof is not a known bootloader, while the GPS verifier only accepts bootloader proofs".to_string(),
+        );
+    }
+    target.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::path::PathBuf;
+
+    const MONOLITH_PROOF: &str =
+        "tests/resources/proofs/starknet/monolith/cairo0_stone5_keccak_160_lsb_example_proof.json";
+
+    fn resource(path: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join(path)
+    }
+
+    #[rstest]
+    // the proof records no commit, so the default Stone version is assumed
+    #[case(None, "stone6")]
+    #[case(Some(StoneVersion::V5), "stone5")]
+    fn test_integrity_verifier_config_stone_version(
+        #[case] stone_version: Option<StoneVersion>,
+        #[case] expected: &str,
+    ) {
+        let config = integrity_verifier_config(
+            &resource(MONOLITH_PROOF),
+            SerializationType::monolith,
+            &stone_version,
+            &CairoVersion::cairo0,
+            &None,
+        )
+        .expect("Failed to derive the verifier config");
+        assert_eq!(config.stone_version, expected);
+        assert_eq!(config.memory_verification, "strict");
+    }
+
+    #[rstest]
+    #[case("7ac17c8ba63a789604350e501558ef0ab990fd88", Some(StoneVersion::V5))]
+    #[case("1414a54", Some(StoneVersion::V6))]
+    #[case("INVALID_COMMIT", None)]
+    #[case("", None)]
+    fn test_stone_version_from_commit(
+        #[case] commit_hash: &str,
+        #[case] expected: Option<StoneVersion>,
+    ) {
+        assert_eq!(stone_version_from_commit(commit_hash), expected);
+    }
+}
@@ -1,7 +1,14 @@
-use crate::config::FriParameters;
+use crate::config::{FriParameters, StarkParameters};
 
 const DEFAULT_N_QUERIES: u32 = 16;
 const DEFAULT_PROOF_OF_WORK_BITS: u32 = 32;
+const DEFAULT_LAST_LAYER_DEGREE_BOUND: u32 = 64;
+const DEFAULT_LOG_N_COSETS: u32 = 4;
+pub const DEFAULT_MAX_FRI_STEP: u32 = 4;
+/// Largest FRI step accepted, so that the `2^step` elements of a queried coset fit in a `u32`.
+pub const MAX_FRI_STEP: u32 = 16;
+/// log₂ of the number of trace rows of a Cairo step, which is the "+4" of the FRI equation.
+pub const LOG_CPU_ROWS_PER_STEP: u32 = 4;
 
 /// Implements ceil(log2(x)).
 fn ceil_log2(x: u32) -> u32 {
@@ -15,6 +22,9 @@ fn ceil_log2(x: u32) -> u32 {
 /// # log₂(#steps) = log₂(last_layer_degree_bound) + ∑fri_step_list - 4
 /// # ∑fri_step_list = log₂(#steps) + 4 - log₂(last_layer_degree_bound)
 ///
+/// The 4 is log₂ of the number of trace rows of a Cairo step, so that the left-hand side is the
+/// log₂ of the trace length. It does not depend on the blowup factor (`log_n_cosets`).
+///
 /// * `nb_steps_log`: Ceiled log₂ of the number of Cairo steps of the program.
 /// * `last_layer_degree_bound_log`: Ceiled log₂ of the last layer degree bound.
 /// * `max_step_value`: Maximum value for each step. All elements will be in the range
@@ -26,7 +36,19 @@ fn compute_fri_steps(
     last_layer_degree_bound_log: u32,
     max_step_value: u32,
 ) -> Vec<u32> {
-    let sum_of_fri_steps = nb_steps_log + 4 - last_layer_degree_bound_log;
+    let sum_of_fri_steps =
+        (nb_steps_log + LOG_CPU_ROWS_PER_STEP).saturating_sub(last_layer_degree_bound_log);
+    split_fri_steps(sum_of_fri_steps, max_step_value)
+}
+
+/// Splits the sum of the FRI steps into steps of at most `max_step_value`, largest first.
+///
+/// * `sum_of_fri_steps`: log₂ of the trace length minus log₂ of the last layer degree bound.
+/// * `max_step_value`: Maximum value for each step.
+///
+/// Returns The FRI steps, without the mandatory 0 step.
+pub fn split_fri_steps(sum_of_fri_steps: u32, max_step_value: u32) -> Vec<u32> {
+    let max_step_value = max_step_value.max(1);
     let quotient = (sum_of_fri_steps / max_step_value) as usize;
     let remainder = sum_of_fri_steps % max_step_value;
 
@@ -38,34 +60,158 @@ fn compute_fri_steps(
     fri_steps
 }
 
+/// Computes the FRI steps list for a given last layer degree bound, starting with the mandatory 0 step.
+///
+/// * `nb_steps`: Number of Cairo steps of the program.
+/// * `last_layer_degree_bound`: The FRI last layer degree bound.
+/// * `max_step_value`: Maximum value for each step.
+///
+/// Returns The FRI steps list.
+pub fn compute_fri_step_list(
+    nb_steps: u32,
+    last_layer_degree_bound: u32,
+    max_step_value: u32,
+) -> Vec<u32> {
+    let mut fri_steps = vec![0];
+    fri_steps.extend(compute_fri_steps(
+        ceil_log2(nb_steps),
+        ceil_log2(last_layer_degree_bound),
+        max_step_value,
+    ));
+    fri_steps
+}
+
+/// Estimated size of the FRI decommitment of a single query, in field elements and hashes.
+///
+/// Folding a layer of log₂ degree `degree_log` by `step` sends the `2^step - 1` other elements
+/// of the queried coset, along with the authentication path of the committed layer, whose
+/// leaves are the cosets of the evaluation domain.
+///
+/// * `degree_log`: log₂ of the degree bound of the layer before folding.
+/// * `step`: The FRI step applied to the layer.
+/// * `log_n_cosets`: log₂ of the blowup factor.
+///
+/// Returns The estimated number of field elements and hashes.
+fn fri_layer_cost(degree_log: u32, step: u32, log_n_cosets: u32) -> u32 {
+    (1 << step) - 1 + degree_log + log_n_cosets - step
+}
+
+/// Computes the FRI steps list minimizing the estimated proof size, starting with the mandatory 0 step.
+///
+/// * `nb_steps`: Number of Cairo steps of the program.
+/// * `last_layer_degree_bound`: The FRI last layer degree bound.
+/// * `log_n_cosets`: log₂ of the blowup factor.
+/// * `max_step_value`: Maximum value for each step, bounded by `MAX_FRI_STEP`.
+///
+/// Returns The FRI steps list.
+fn compute_min_proof_size_fri_step_list(
+    nb_steps: u32,
+    last_layer_degree_bound: u32,
+    log_n_cosets: u32,
+    max_step_value: u32,
+) -> Vec<u32> {
+    let last_layer_degree_bound_log = ceil_log2(last_layer_degree_bound);
+    let trace_degree_log = ceil_log2(nb_steps) + LOG_CPU_ROWS_PER_STEP;
+    let sum_of_fri_steps = trace_degree_log.saturating_sub(last_layer_degree_bound_log) as usize;
+    let max_step_value = max_step_value.clamp(1, MAX_FRI_STEP);
+
+    // best[s] is the minimal cost of folding the last `s` degrees, along with the first step to apply
+    let mut best: Vec<(u32, u32)> = vec![(0, 0); sum_of_fri_steps + 1];
+    for remaining in 1..=sum_of_fri_steps {
+        let degree_log = last_layer_degree_bound_log + remaining as u32;
+        best[remaining] = (1..=max_step_value.min(remaining as u32))
+            .rev()
+            .map(|step| {
+                let cost = fri_layer_cost(degree_log, step, log_n_cosets)
+                    + best[remaining - step as usize].0;
+                (cost, step)
+            })
+            .min_by_key(|(cost, _)| *cost)
+            .unwrap();
+    }
+
+    let mut fri_steps = vec![0];
+    let mut remaining = sum_of_fri_steps;
+    while remaining > 0 {
+        let step = best[remaining].1;
+        fri_steps.push(step);
+        remaining -= step as usize;
+    }
+    fri_steps
+}
+
+/// Derives the FRI parameters of a proof from its number of Cairo steps.
+///
+/// The parameters given in `stark_parameters` are kept, and only the missing ones are computed,
+/// so that the FRI steps list is consistent with the requested last layer degree bound.
 pub trait FriComputer {
-    fn compute_fri_parameters(&self, nb_steps: u32) -> FriParameters;
+    fn compute_fri_parameters(
+        &self,
+        nb_steps: u32,
+        stark_parameters: &StarkParameters,
+        max_step_value: u32,
+    ) -> FriParameters;
 }
 
+/// Uses the largest steps first, which commits to the fewest and smallest FRI layers and
+/// minimizes the prover time.
 pub struct DefaultFriComputer;
 
 impl FriComputer for DefaultFriComputer {
-    fn compute_fri_parameters(&self, nb_steps: u32) -> FriParameters {
-        let last_layer_degree_bound = 64;
-
-        let nb_steps_log = ceil_log2(nb_steps);
-        let last_layer_degree_bound_log = ceil_log2(last_layer_degree_bound);
-        let max_step_value = 4;
-
-        // The first FRI step must be 0
-        let mut fri_steps = vec![0];
-        fri_steps.extend(compute_fri_steps(
-            nb_steps_log,
-            last_layer_degree_bound_log,
-            max_step_value,
-        ));
+    fn compute_fri_parameters(
+        &self,
+        nb_steps: u32,
+        stark_parameters: &StarkParameters,
+        max_step_value: u32,
+    ) -> FriParameters {
+        complete_fri_parameters(stark_parameters, |last_layer_degree_bound| {
+            compute_fri_step_list(nb_steps, last_layer_degree_bound, max_step_value)
+        })
+    }
+}
 
-        FriParameters {
-            fri_step_list: Some(fri_steps),
-            last_layer_degree_bound: Some(last_layer_degree_bound),
-            n_queries: Some(DEFAULT_N_QUERIES),
-            proof_of_work_bits: Some(DEFAULT_PROOF_OF_WORK_BITS),
-        }
+/// Chooses the steps minimizing the estimated size of the FRI decommitments of the proof.
+pub struct ProofSizeFriComputer;
+
+impl FriComputer for ProofSizeFriComputer {
+    fn compute_fri_parameters(
+        &self,
+        nb_steps: u32,
+        stark_parameters: &StarkParameters,
+        max_step_value: u32,
+    ) -> FriParameters {
+        let log_n_cosets = stark_parameters
+            .log_n_cosets
+            .map_or(DEFAULT_LOG_N_COSETS, |log_n_cosets| log_n_cosets as u32);
+        complete_fri_parameters(stark_parameters, |last_layer_degree_bound| {
+            compute_min_proof_size_fri_step_list(
+                nb_steps,
+                last_layer_degree_bound,
+                log_n_cosets,
+                max_step_value,
+            )
+        })
+    }
+}
+
+fn complete_fri_parameters(
+    stark_parameters: &StarkParameters,
+    compute_fri_step_list: impl FnOnce(u32) -> Vec<u32>,
+) -> FriParameters {
+    let fri = &stark_parameters.fri;
+    let last_layer_degree_bound = fri
+        .last_layer_degree_bound
+        .unwrap_or(DEFAULT_LAST_LAYER_DEGREE_BOUND);
+    let fri_step_list = fri
+        .fri_step_list
+        .clone()
+        .unwrap_or_else(|| compute_fri_step_list(last_layer_degree_bound));
+
+    FriParameters {
+        fri_step_list: Some(fri_step_list),
+        last_layer_degree_bound: Some(last_layer_degree_bound),
+        n_queries: Some(fri.n_queries.unwrap_or(DEFAULT_N_QUERIES)),
+        proof_of_work_bits: Some(fri.proof_of_work_bits.unwrap_or(DEFAULT_PROOF_OF_WORK_BITS)),
     }
 }
 
@@ -74,6 +220,28 @@ mod tests {
     use super::*;
     use rstest::rstest;
 
+    fn stark_parameters(last_layer_degree_bound: Option<u32>) -> StarkParameters {
+        StarkParameters {
+            fri: FriParameters {
+                fri_step_list: None,
+                last_layer_degree_bound,
+                n_queries: None,
+                proof_of_work_bits: None,
+            },
+            log_n_cosets: Some(4),
+        }
+    }
+
+    fn estimated_cost(nb_steps: u32, fri_step_list: &[u32]) -> u32 {
+        let mut degree_log = ceil_log2(nb_steps) + LOG_CPU_ROWS_PER_STEP;
+        let mut cost = 0;
+        for step in fri_step_list.iter().filter(|step| **step > 0) {
+            cost += fri_layer_cost(degree_log, *step, 4);
+            degree_log -= step;
+        }
+        cost
+    }
+
     #[rstest]
     #[case(2, 1)]
     #[case(32, 5)]
@@ -90,11 +258,91 @@ mod tests {
     #[case(768, vec ! [0, 4, 4])]
     fn test_compute_fri_parameters_default(#[case] nb_steps: u32, #[case] expected: Vec<u32>) {
         let expected_last_layer_degree_bound = 64;
-        let fri_parameters = DefaultFriComputer.compute_fri_parameters(nb_steps);
+        let fri_parameters = DefaultFriComputer.compute_fri_parameters(
+            nb_steps,
+            &stark_parameters(None),
+            DEFAULT_MAX_FRI_STEP,
+        );
         assert_eq!(fri_parameters.fri_step_list, Some(expected));
         assert_eq!(
             fri_parameters.last_layer_degree_bound,
             Some(expected_last_layer_degree_bound)
         );
     }
+
+    #[rstest]
+    #[case(32768, 128, 4, vec ! [0, 4, 4, 4])]
+    #[case(32768, 32, 3, vec ! [0, 3, 3, 3, 3, 2])]
+    #[case(16, 256, 4, vec ! [0])]
+    fn test_compute_fri_parameters_last_layer_degree_bound(
+        #[case] nb_steps: u32,
+        #[case] last_layer_degree_bound: u32,
+        #[case] max_step_value: u32,
+        #[case] expected: Vec<u32>,
+    ) {
+        let fri_parameters = DefaultFriComputer.compute_fri_parameters(
+            nb_steps,
+            &stark_parameters(Some(last_layer_degree_bound)),
+            max_step_value,
+        );
+        assert_eq!(fri_parameters.fri_step_list, Some(expected));
+        assert_eq!(
+            fri_parameters.last_layer_degree_bound,
+            Some(last_layer_degree_bound)
+        );
+    }
+
+    #[rstest]
+    #[case(768, 64)]
+    #[case(32768, 64)]
+    #[case(524288, 32)]
+    #[case(1 << 24, 128)]
+    fn test_compute_fri_parameters_proof_size(
+        #[case] nb_steps: u32,
+        #[case] last_layer_degree_bound: u32,
+    ) {
+        let parameters = stark_parameters(Some(last_layer_degree_bound));
+        let fri_step_list = ProofSizeFriComputer
+            .compute_fri_parameters(nb_steps, &parameters, DEFAULT_MAX_FRI_STEP)
+            .fri_step_list
+            .unwrap();
+        let default_fri_step_list = DefaultFriComputer
+            .compute_fri_parameters(nb_steps, &parameters, DEFAULT_MAX_FRI_STEP)
+            .fri_step_list
+            .unwrap();
+
+        assert_eq!(fri_step_list[0], 0);
+        assert!(fri_step_list
+            .iter()
+            .all(|step| *step <= DEFAULT_MAX_FRI_STEP));
+        assert_eq!(
+            fri_step_list.iter().sum::<u32>() + ceil_log2(last_layer_degree_bound),
+            ceil_log2(nb_steps) + LOG_CPU_ROWS_PER_STEP
+        );
+        assert!(
+            estimated_cost(nb_steps, &fri_step_list)
+                <= estimated_cost(nb_steps, &default_fri_step_list)
+        );
+    }
+
+    #[test]
+    fn test_compute_fri_parameters_keeps_step_list() {
+        let mut parameters = stark_parameters(None);
+        parameters.fri.fri_step_list = Some(vec![0, 2, 2]);
+        let fri_parameters =
+            ProofSizeFriComputer.compute_fri_parameters(32768, &parameters, DEFAULT_MAX_FRI_STEP);
+        assert_eq!(fri_parameters.fri_step_list, Some(vec![0, 2, 2]));
+    }
+
+    #[rstest]
+    #[case(32)]
+    #[case(u32::MAX)]
+    fn test_compute_fri_parameters_proof_size_large_max_step(#[case] max_step_value: u32) {
+        let fri_step_list = ProofSizeFriComputer
+            .compute_fri_parameters(1 << 24, &stark_parameters(Some(1)), max_step_value)
+            .fri_step_list
+            .unwrap();
+        assert!(fri_step_list.iter().all(|step| *step <= MAX_FRI_STEP));
+        assert_eq!(fri_step_list.iter().sum::<u32>(), 28);
+    }
 }
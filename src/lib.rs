@@ -1,10 +1,14 @@
 pub mod args;
 pub mod bootloader;
 pub mod cairo;
+pub mod compat;
 pub mod fri;
+pub mod manifest;
+pub mod params;
 pub mod prover;
 pub mod serialize;
 pub mod sharp;
+pub mod target;
 pub mod utils;
 pub mod verifier;
 
@@ -1,10 +1,12 @@
 use clap::Parser;
 use stone_cli::args::Cli;
 use stone_cli::bootloader::run_bootloader;
-use stone_cli::cairo::run_cairo;
+use stone_cli::cairo::{run_cairo, run_cairo_to_pie};
+use stone_cli::compat::check_compat;
+use stone_cli::params::run_params;
 use stone_cli::prover::{run_stone_prover, run_stone_prover_bootloader};
 use stone_cli::serialize::serialize_proof;
-use stone_cli::utils::cleanup_tmp_files;
+use stone_cli::utils::{cleanup_tmp_files, write_json_to_file};
 use stone_cli::verifier::run_stone_verifier;
 use tempfile::Builder;
 
@@ -17,7 +19,18 @@ fn main() -> anyhow::Result<()> {
 
     let cli = Cli::parse();
     match cli {
-        Cli::Prove(args) => {
+        Cli::Prove(args) if args.cairo_pie_output.is_some() => {
+            let cairo_pie_output = args.cairo_pie_output.clone().unwrap();
+            let result = run_cairo_to_pie(&args, &cairo_pie_output, &tmp_dir)
+                .map_err(|e| anyhow::anyhow!("Failed to run cairo: {}", e));
+            cleanup_tmp_files(&tmp_dir);
+            result?;
+            println!("Created Cairo PIE at {:?}", cairo_pie_output);
+            Ok(())
+        }
+        Cli::Prove(mut args) => {
+            args.apply_preset()
+                .map_err(|e| anyhow::anyhow!("Invalid preset: {}", e))?;
             let result = run_cairo(&args, &tmp_dir)
                 .map_err(|e| anyhow::anyhow!("Failed to run cairo: {}", e))
                 .and_then(|run_cairo_result| {
@@ -41,7 +54,9 @@ fn main() -> anyhow::Result<()> {
                 }
             }
         }
-        Cli::ProveBootloader(args) => {
+        Cli::ProveBootloader(mut args) => {
+            args.apply_preset()
+                .map_err(|e| anyhow::anyhow!("Invalid preset: {}", e))?;
             let tmp_dir = Builder::new()
                 .prefix("stone-cli-")
                 .tempdir()
@@ -69,11 +84,43 @@ fn main() -> anyhow::Result<()> {
                 }
             }
         }
+        Cli::Params(args) => {
+            let result = run_params(&args, &tmp_dir)
+                .map_err(|e| anyhow::anyhow!("Failed to generate parameters: {}", e));
+            cleanup_tmp_files(&tmp_dir);
+            let parameters = result?;
+            println!(
+                "Created prover parameters at {:?} for {} steps: n_queries {}, log_n_cosets {}, proof_of_work_bits {}, last_layer_degree_bound {}, fri_step_list {:?}",
+                args.output,
+                parameters.n_steps,
+                parameters.n_queries,
+                parameters.log_n_cosets,
+                parameters.proof_of_work_bits,
+                parameters.last_layer_degree_bound,
+                parameters.fri_step_list
+            );
+            println!("Conjectured security: {} bits", parameters.security_bits);
+            Ok(())
+        }
         Cli::Verify(args) => {
             run_stone_verifier(args).map_err(|e| anyhow::anyhow!("Verification failed: {}", e))
         }
         Cli::SerializeProof(args) => {
-            serialize_proof(args).map_err(|e| anyhow::anyhow!("Serialization failed: {}", e))
+            let report = serialize_proof(args)
+                .map_err(|e| anyhow::anyhow!("Serialization failed: {}", e))?;
+            if let Some(report) = report {
+                print!("{}", report);
+            }
+            Ok(())
+        }
+        Cli::CheckCompat(args) => {
+            let report = check_compat(&args)
+                .map_err(|e| anyhow::anyhow!("Failed to check the proof: {}", e))?;
+            if let Some(output) = &args.output {
+                write_json_to_file(&report, output)?;
+            }
+            print!("{}", report);
+            Ok(())
         }
     }
 }
@@ -0,0 +1,210 @@
+use crate::utils::parse_felt;
+use cairo_vm::Felt252;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    IO(#[from] std::io::Error),
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+    #[error(transparent)]
+    Toml(#[from] toml::de::Error),
+    #[error("The task manifest does not contain any task")]
+    NoTasks,
+    #[error("Task {index}: file {path:?} does not exist")]
+    FileNotFound { index: usize, path: PathBuf },
+    #[error("Task {index}: an input file is not supported for Cairo PIE tasks")]
+    InputFileNotSupported { index: usize },
+    #[error("Task {index}: the label {label:?} is already used by another task")]
+    DuplicateLabel { index: usize, label: String },
+    #[error("Task {index}: invalid expected output {value:?}")]
+    InvalidExpectedOutput { index: usize, value: String },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    /// Compiled Cairo 0 program, whose input file is a JSON object used as program input
+    Program,
+    /// Cairo PIE zip file
+    Pie,
+    /// Cairo 1 source or Sierra file, whose input file uses the `--program_input` format
+    Cairo1,
+}
+
+/// A task of the bootloader job, as listed in the task manifest
+#[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TaskEntry {
+    #[serde(rename = "type")]
+    pub kind: TaskKind,
+    pub path: PathBuf,
+    pub input_file: Option<PathBuf>,
+    pub label: Option<String>,
+    /// Expected output of the task, as hex or decimal strings
+    pub expected_output: Option<Vec<String>>,
+}
+
+impl TaskEntry {
+    /// Returns the expected output of the task as field elements
+    ///
+    /// Values are validated by `load_task_manifest`, invalid values are skipped.
+    pub fn expected_output_felts(&self) -> Option<Vec<Felt252>> {
+        self.expected_output.as_ref().map(|output| {
+            output
+                .iter()
+                .filter_map(|value| parse_felt(value))
+                .collect()
+        })
+    }
+}
+
+/// Tasks of a bootloader job, read from the file given with `--tasks`
+///
+/// In JSON, the manifest is an object with a `tasks` array. In TOML, each task is a
+/// `[[tasks]]` table.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TaskManifest {
+    pub tasks: Vec<TaskEntry>,
+}
+
+/// Loads and validates a task manifest
+///
+/// Relative paths in the manifest are resolved against the directory of the manifest. All tasks
+/// are validated before returning, so that no task is run for an invalid manifest.
+///
+/// # Arguments
+///
+/// * `path` - Path to the manifest, parsed as TOML if it has a `.toml` extension and as JSON otherwise
+///
+/// # Returns
+///
+/// A `Result` containing the validated `TaskManifest`, or an `Error` describing the first invalid task
+pub fn load_task_manifest(path: &Path) -> Result<TaskManifest, Error> {
+    let content = std::fs::read_to_string(path)?;
+    let mut manifest: TaskManifest = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&content)?,
+        _ => serde_json::from_str(&content)?,
+    };
+
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    for task in manifest.tasks.iter_mut() {
+        task.path = base_dir.join(&task.path);
+        task.input_file = task.input_file.as_ref().map(|file| base_dir.join(file));
+    }
+
+    validate_task_manifest(&manifest)?;
+    Ok(manifest)
+}
+
+fn validate_task_manifest(manifest: &TaskManifest) -> Result<(), Error> {
+    if manifest.tasks.is_empty() {
+        return Err(Error::NoTasks);
+    }
+
+    let mut labels = HashSet::new();
+    for (index, task) in manifest.tasks.iter().enumerate() {
+        for path in std::iter::once(&task.path).chain(task.input_file.iter()) {
+            if !path.exists() {
+                return Err(Error::FileNotFound {
+                    index,
+                    path: path.clone(),
+                });
+            }
+        }
+        if task.kind == TaskKind::Pie && task.input_file.is_some() {
+            return Err(Error::InputFileNotSupported { index });
+        }
+        if let Some(label) = &task.label {
+            if !labels.insert(label) {
+                return Err(Error::DuplicateLabel {
+                    index,
+                    label: label.clone(),
+                });
+            }
+        }
+        if let Some(value) = task
+            .expected_output
+            .iter()
+            .flatten()
+            .find(|value| parse_felt(value).is_none())
+        {
+            return Err(Error::InvalidExpectedOutput {
+                index,
+                value: value.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn task(kind: TaskKind, input_file: Option<&str>, label: Option<&str>) -> TaskEntry {
+        TaskEntry {
+            kind,
+            path: PathBuf::from(file!()),
+            input_file: input_file.map(PathBuf::from),
+            label: label.map(String::from),
+            expected_output: None,
+        }
+    }
+
+    #[rstest]
+    #[case(vec![task(TaskKind::Program, None, Some("a")), task(TaskKind::Pie, None, Some("b"))], true)]
+    #[case(vec![], false)]
+    #[case(vec![task(TaskKind::Pie, Some(file!()), None)], false)]
+    #[case(vec![task(TaskKind::Cairo1, Some("missing_input.txt"), None)], false)]
+    #[case(vec![task(TaskKind::Program, None, Some("a")), task(TaskKind::Cairo1, None, Some("a"))], false)]
+    fn test_validate_task_manifest(#[case] tasks: Vec<TaskEntry>, #[case] expected_ok: bool) {
+        let result = validate_task_manifest(&TaskManifest { tasks });
+        assert_eq!(result.is_ok(), expected_ok);
+    }
+
+    #[test]
+    fn test_validate_task_manifest_expected_output() {
+        let mut entry = task(TaskKind::Program, None, None);
+        entry.expected_output = Some(vec!["0x10".to_string(), "not a felt".to_string()]);
+        let result = validate_task_manifest(&TaskManifest { tasks: vec![entry] });
+        assert!(matches!(
+            result,
+            Err(Error::InvalidExpectedOutput { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_toml_manifest() {
+        let manifest: TaskManifest = toml::from_str(
+            r#"
+            [[tasks]]
+            type = "cairo1"
+            path = "fibonacci.cairo"
+            label = "fib"
+            expected_output = ["0x37"]
+
+            [[tasks]]
+            type = "pie"
+            path = "my pies/fibonacci.zip"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(manifest.tasks.len(), 2);
+        assert_eq!(manifest.tasks[0].kind, TaskKind::Cairo1);
+        assert_eq!(
+            manifest.tasks[0].expected_output_felts(),
+            Some(vec![Felt252::from(0x37)])
+        );
+        assert_eq!(
+            manifest.tasks[1].path,
+            PathBuf::from("my pies/fibonacci.zip")
+        );
+    }
+}
@@ -0,0 +1,352 @@
+use crate::args::{ParamsArgs, ProveArgs, StoneVersion};
+use crate::cairo::run_cairo;
+use crate::config::{FriParameters, ProverConfig, ProverParametersConfig, StarkParameters};
+use crate::fri::{compute_fri_step_list, DEFAULT_MAX_FRI_STEP, LOG_CPU_ROWS_PER_STEP};
+use crate::prover::PublicInput;
+use crate::utils::write_json_to_file;
+use serde::Serialize;
+use std::path::PathBuf;
+use thiserror::Error;
+
+const DEFAULT_LOG_N_COSETS: u32 = 4;
+const DEFAULT_PROOF_OF_WORK_BITS: u32 = 32;
+const DEFAULT_LAST_LAYER_DEGREE_BOUND: u32 = 64;
+// Largest log₂ of the blowup factor solved for, as the evaluation domain grows with it
+const MAX_LOG_N_COSETS: u32 = 16;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    IO(#[from] std::io::Error),
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+    #[error("Failed to run the Cairo program: {0}")]
+    CairoRun(anyhow::Error),
+    #[error("{name} should be positive")]
+    NotPositive { name: &'static str },
+    #[error("The proof of work bits ({proof_of_work_bits}) exceed the target security of {security_bits} bits")]
+    ProofOfWorkBitsTooLarge {
+        proof_of_work_bits: u32,
+        security_bits: u32,
+    },
+    #[error("The given parameters reach {achieved} bits of security, below the target of {security_bits} bits. Increase --n_queries, --log_n_cosets or --proof_of_work_bits, or leave one of them unset")]
+    InsufficientSecurity { achieved: u32, security_bits: u32 },
+    #[error("The conjectured security of {n_queries} queries, log_n_cosets {log_n_cosets} and {proof_of_work_bits} proof of work bits overflows")]
+    SecurityBitsOverflow {
+        n_queries: u32,
+        log_n_cosets: u32,
+        proof_of_work_bits: u32,
+    },
+    #[error("{n_queries} queries cannot reach {security_bits} bits of security with {proof_of_work_bits} proof of work bits, as log_n_cosets is at most {max_log_n_cosets}. Increase --n_queries or --proof_of_work_bits")]
+    UnreachableSecurity {
+        n_queries: u32,
+        proof_of_work_bits: u32,
+        security_bits: u32,
+        max_log_n_cosets: u32,
+    },
+    #[error("The trace length of {0} steps overflows")]
+    TooManySteps(u32),
+    #[error("The last layer degree bound should be a power of two, got {0}")]
+    LastLayerDegreeBoundNotPowerOfTwo(u32),
+    #[error("The last layer degree bound {last_layer_degree_bound} is too large for {n_steps} steps, the maximum is {max}")]
+    LastLayerDegreeBoundTooLarge {
+        last_layer_degree_bound: u32,
+        n_steps: u32,
+        max: u32,
+    },
+}
+
+/// Parameters that are fixed by the user instead of being solved for
+#[derive(Debug, Default, Clone)]
+pub struct SecurityPins {
+    pub n_queries: Option<u32>,
+    pub log_n_cosets: Option<u32>,
+    pub proof_of_work_bits: Option<u32>,
+    pub last_layer_degree_bound: Option<u32>,
+}
+
+/// Solved prover parameters along with the conjectured security they reach
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct SecurityParameters {
+    pub n_steps: u32,
+    pub n_queries: u32,
+    pub log_n_cosets: u32,
+    pub proof_of_work_bits: u32,
+    pub last_layer_degree_bound: u32,
+    pub fri_step_list: Vec<u32>,
+    pub security_bits: u32,
+}
+
+/// Solves for the prover parameters reaching a target conjectured security
+///
+/// The conjectured security of a Stone proof is `n_queries * log_n_cosets + proof_of_work_bits`.
+/// Parameters that are not pinned default to a blowup factor of 16 (`log_n_cosets` 4) and 32
+/// proof of work bits, and the number of queries is then the smallest that reaches the target.
+/// If the number of queries is pinned, the blowup factor is solved for instead, up to a
+/// `log_n_cosets` of `MAX_LOG_N_COSETS`.
+///
+/// # Arguments
+///
+/// * `n_steps` - The number of Cairo steps of the program
+/// * `security_bits` - The target conjectured security, in bits
+/// * `pins` - Parameters fixed by the user
+///
+/// # Returns
+///
+/// A `Result` containing the `SecurityParameters`, or an `Error` if the pinned parameters cannot reach the target
+pub fn solve_security_parameters(
+    n_steps: u32,
+    security_bits: u32,
+    pins: &SecurityPins,
+) -> Result<SecurityParameters, Error> {
+    for (name, value) in [
+        ("n_steps", Some(n_steps)),
+        ("security_bits", Some(security_bits)),
+        ("n_queries", pins.n_queries),
+        ("log_n_cosets", pins.log_n_cosets),
+    ] {
+        if value == Some(0) {
+            return Err(Error::NotPositive { name });
+        }
+    }
+
+    let proof_of_work_bits = pins
+        .proof_of_work_bits
+        .unwrap_or(DEFAULT_PROOF_OF_WORK_BITS.min(security_bits));
+    if proof_of_work_bits > security_bits {
+        return Err(Error::ProofOfWorkBitsTooLarge {
+            proof_of_work_bits,
+            security_bits,
+        });
+    }
+    let query_bits = security_bits - proof_of_work_bits;
+
+    let (n_queries, log_n_cosets) = match (pins.n_queries, pins.log_n_cosets) {
+        (Some(n_queries), Some(log_n_cosets)) => (n_queries, log_n_cosets),
+        (Some(n_queries), None) => {
+            let log_n_cosets = query_bits.div_ceil(n_queries).max(1);
+            if log_n_cosets > MAX_LOG_N_COSETS {
+                return Err(Error::UnreachableSecurity {
+                    n_queries,
+                    proof_of_work_bits,
+                    security_bits,
+                    max_log_n_cosets: MAX_LOG_N_COSETS,
+                });
+            }
+            (n_queries, log_n_cosets)
+        }
+        (None, log_n_cosets) => {
+            let log_n_cosets = log_n_cosets.unwrap_or(DEFAULT_LOG_N_COSETS);
+            (query_bits.div_ceil(log_n_cosets).max(1), log_n_cosets)
+        }
+    };
+    let achieved = n_queries
+        .checked_mul(log_n_cosets)
+        .and_then(|query_bits| query_bits.checked_add(proof_of_work_bits))
+        .ok_or(Error::SecurityBitsOverflow {
+            n_queries,
+            log_n_cosets,
+            proof_of_work_bits,
+        })?;
+    if achieved < security_bits {
+        return Err(Error::InsufficientSecurity {
+            achieved,
+            security_bits,
+        });
+    }
+
+    let max_last_layer_degree_bound = n_steps
+        .checked_next_power_of_two()
+        .and_then(|n_steps| n_steps.checked_mul(1 << LOG_CPU_ROWS_PER_STEP))
+        .ok_or(Error::TooManySteps(n_steps))?;
+    let last_layer_degree_bound = match pins.last_layer_degree_bound {
+        Some(bound) if !bound.is_power_of_two() => {
+            return Err(Error::LastLayerDegreeBoundNotPowerOfTwo(bound));
+        }
+        Some(bound) if bound > max_last_layer_degree_bound => {
+            return Err(Error::LastLayerDegreeBoundTooLarge {
+                last_layer_degree_bound: bound,
+                n_steps,
+                max: max_last_layer_degree_bound,
+            });
+        }
+        Some(bound) => bound,
+        None => DEFAULT_LAST_LAYER_DEGREE_BOUND.min(max_last_layer_degree_bound),
+    };
+    let fri_step_list =
+        compute_fri_step_list(n_steps, last_layer_degree_bound, DEFAULT_MAX_FRI_STEP);
+
+    Ok(SecurityParameters {
+        n_steps,
+        n_queries,
+        log_n_cosets,
+        proof_of_work_bits,
+        last_layer_degree_bound,
+        fri_step_list,
+        security_bits: achieved,
+    })
+}
+
+/// Generates the prover parameters for a target security and writes them to `args.output`
+///
+/// The number of steps is either given directly or obtained by running the Cairo program.
+///
+/// # Arguments
+///
+/// * `args` - Arguments for generating parameters
+/// * `tmp_dir` - A temporary directory to store intermediate files
+///
+/// # Returns
+///
+/// A `Result` containing the solved `SecurityParameters`, or an `Error` on failure
+pub fn run_params(
+    args: &ParamsArgs,
+    tmp_dir: &tempfile::TempDir,
+) -> Result<SecurityParameters, Error> {
+    let n_steps = match (args.n_steps, &args.cairo_program) {
+        (Some(n_steps), _) => n_steps,
+        (None, cairo_program) => {
+            let prove_args = ProveArgs {
+                cairo_version: args.cairo_version.clone(),
+                cairo_program: cairo_program.clone(),
+                cairo_pie: None,
+                program_input: args.program_input.clone(),
+                program_input_file: args.program_input_file.clone(),
+                layout: args.layout.clone(),
+                prover_config_file: None,
+                parameter_file: None,
+                preset: None,
+                output: PathBuf::new(),
+                cairo_pie_output: None,
+                parameter_config: ProverParametersConfig::default(),
+                prover_config: ProverConfig::default(),
+                stone_version: StoneVersion::V6,
+                bench_memory: None,
+            };
+            let run_result = run_cairo(&prove_args, tmp_dir).map_err(Error::CairoRun)?;
+            let public_input: PublicInput =
+                serde_json::from_str(&std::fs::read_to_string(run_result.air_public_input)?)?;
+            public_input.n_steps
+        }
+    };
+
+    let pins = SecurityPins {
+        n_queries: args.n_queries,
+        log_n_cosets: args.log_n_cosets,
+        proof_of_work_bits: args.proof_of_work_bits,
+        last_layer_degree_bound: args.last_layer_degree_bound,
+    };
+    let security_parameters = solve_security_parameters(n_steps, args.security_bits, &pins)?;
+
+    let prover_parameters =
+        ProverParametersConfig::default().with_stark_parameters(StarkParameters {
+            fri: FriParameters {
+                fri_step_list: Some(security_parameters.fri_step_list.clone()),
+                last_layer_degree_bound: Some(security_parameters.last_layer_degree_bound),
+                n_queries: Some(security_parameters.n_queries),
+                proof_of_work_bits: Some(security_parameters.proof_of_work_bits),
+            },
+            log_n_cosets: Some(security_parameters.log_n_cosets as i32),
+        });
+    write_json_to_file(prover_parameters, &args.output)?;
+
+    Ok(security_parameters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(96, SecurityPins::default(), 16, 4, 32)]
+    #[case(128, SecurityPins::default(), 24, 4, 32)]
+    #[case(100, SecurityPins::default(), 17, 4, 32)]
+    #[case(20, SecurityPins::default(), 1, 4, 20)]
+    #[case(96, SecurityPins { n_queries: Some(8), ..Default::default() }, 8, 8, 32)]
+    #[case(80, SecurityPins { log_n_cosets: Some(2), proof_of_work_bits: Some(24), ..Default::default() }, 28, 2, 24)]
+    fn test_solve_security_parameters(
+        #[case] security_bits: u32,
+        #[case] pins: SecurityPins,
+        #[case] expected_n_queries: u32,
+        #[case] expected_log_n_cosets: u32,
+        #[case] expected_proof_of_work_bits: u32,
+    ) {
+        let parameters = solve_security_parameters(32768, security_bits, &pins).unwrap();
+        assert_eq!(parameters.n_queries, expected_n_queries);
+        assert_eq!(parameters.log_n_cosets, expected_log_n_cosets);
+        assert_eq!(parameters.proof_of_work_bits, expected_proof_of_work_bits);
+        assert!(parameters.security_bits >= security_bits);
+        assert_eq!(parameters.fri_step_list, vec![0, 4, 4, 4, 1]);
+    }
+
+    #[rstest]
+    #[case(96, SecurityPins { n_queries: Some(1), ..Default::default() })]
+    #[case(128, SecurityPins { n_queries: Some(4), proof_of_work_bits: Some(0), ..Default::default() })]
+    fn test_solve_security_parameters_unreachable(
+        #[case] security_bits: u32,
+        #[case] pins: SecurityPins,
+    ) {
+        let result = solve_security_parameters(32768, security_bits, &pins);
+        assert!(matches!(
+            result,
+            Err(Error::UnreachableSecurity {
+                max_log_n_cosets: MAX_LOG_N_COSETS,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_solve_security_parameters_insufficient() {
+        let pins = SecurityPins {
+            n_queries: Some(10),
+            log_n_cosets: Some(4),
+            proof_of_work_bits: Some(20),
+            ..Default::default()
+        };
+        let result = solve_security_parameters(32768, 96, &pins);
+        assert!(matches!(
+            result,
+            Err(Error::InsufficientSecurity { achieved: 60, .. })
+        ));
+    }
+
+    #[rstest]
+    #[case(16, Some(48), false)]
+    #[case(16, Some(512), false)]
+    #[case(16, Some(256), true)]
+    #[case(2, None, true)]
+    fn test_solve_last_layer_degree_bound(
+        #[case] n_steps: u32,
+        #[case] last_layer_degree_bound: Option<u32>,
+        #[case] expected_ok: bool,
+    ) {
+        let pins = SecurityPins {
+            last_layer_degree_bound,
+            ..Default::default()
+        };
+        let result = solve_security_parameters(n_steps, 96, &pins);
+        assert_eq!(result.is_ok(), expected_ok);
+    }
+
+    #[test]
+    fn test_solve_security_parameters_overflowing_security_bits() {
+        let pins = SecurityPins {
+            n_queries: Some(u32::MAX),
+            log_n_cosets: Some(2),
+            ..Default::default()
+        };
+        let result = solve_security_parameters(32768, 96, &pins);
+        assert!(matches!(result, Err(Error::SecurityBitsOverflow { .. })));
+    }
+
+    #[rstest]
+    #[case(u32::MAX)]
+    #[case((1 << 31) + 1)]
+    #[case(1 << 28)]
+    fn test_solve_security_parameters_too_many_steps(#[case] n_steps: u32) {
+        let result = solve_security_parameters(n_steps, 96, &SecurityPins::default());
+        assert!(matches!(result, Err(Error::TooManySteps(_))));
+    }
+}
@@ -1,11 +1,15 @@
 pub mod config;
 
-use crate::args::{LayoutName, ProveArgs, ProveBootloaderArgs, StoneVersion};
+use crate::args::{LayoutName, ProveArgs, ProveBootloaderArgs, StoneVersion, VerifierTarget};
+use crate::fri::LOG_CPU_ROWS_PER_STEP;
 use crate::sharp::{resolve_automatic_layout, DynamicParamsResponse};
 use crate::utils::write_json_to_file;
 use crate::{path_stone_v5_prover, path_stone_v6_prover};
 use cairo_vm::air_public_input::{MemorySegmentAddresses, PublicMemoryEntry};
-use config::{ProverConfig, ProverParametersConfig};
+use config::{
+    load_parameter_file, load_prover_config_file, ProverConfig, ProverParametersConfig,
+    ValidationError,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -24,12 +28,34 @@ pub struct PublicInput {
     pub dynamic_params: Option<HashMap<String, u32>>,
 }
 
+impl PublicInput {
+    /// log₂ of the number of trace rows of a Cairo step of the layout
+    ///
+    /// A step takes 16 rows in the static layouts, while the dynamic layout multiplies them by
+    /// the `cpu_component_step` of its parameters.
+    pub fn log_cpu_rows_per_step(&self) -> u32 {
+        let cpu_component_step = self
+            .dynamic_params
+            .as_ref()
+            .and_then(|params| params.get("cpu_component_step"))
+            .copied()
+            .unwrap_or(1);
+        LOG_CPU_ROWS_PER_STEP + cpu_component_step.max(1).ilog2()
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum ProverError {
     #[error("{0}")]
     IoError(#[from] std::io::Error),
     #[error("{0}")]
     CommandError(ProverCommandError),
+    #[error("{0}")]
+    ValidationError(#[from] ValidationError),
+    #[error("Invalid public input: {0}")]
+    InvalidPublicInput(serde_json::Error),
+    #[error("Invalid prover parameters: {0}")]
+    InvalidParameters(serde_json::Error),
     #[error("heaptrack command not found. Please install heaptrack to use memory benchmarking.")]
     HeaptrackNotFound,
 }
@@ -103,6 +129,7 @@ pub fn run_stone_prover(
     run_stone_prover_internal(
         &prove_args.parameter_config,
         prove_args.parameter_file.as_ref(),
+        None,
         &prove_args.prover_config,
         prove_args.prover_config_file.as_ref(),
         &prove_args.output,
@@ -138,10 +165,11 @@ pub fn run_stone_prover_bootloader(
     run_stone_prover_internal(
         &prove_bootloader_args.parameter_config,
         prove_bootloader_args.parameter_file.as_ref(),
+        Some(&prove_bootloader_args.verifier_target),
         &prove_bootloader_args.prover_config,
         prove_bootloader_args.prover_config_file.as_ref(),
         &prove_bootloader_args.output,
-        &StoneVersion::V5,
+        &prove_bootloader_args.stone_version,
         air_public_input,
         air_private_input,
         tmp_dir,
@@ -155,6 +183,7 @@ pub fn run_stone_prover_bootloader(
 fn run_stone_prover_internal(
     parameter_config: &ProverParametersConfig,
     parameter_file: Option<&PathBuf>,
+    verifier_target: Option<&VerifierTarget>,
     prover_config: &ProverConfig,
     prover_config_file: Option<&PathBuf>,
     output_file: &PathBuf,
@@ -165,14 +194,26 @@ fn run_stone_prover_internal(
     bench_memory: Option<bool>,
 ) -> Result<(), ProverError> {
     let tmp_prover_parameters_path = tmp_dir.path().join("prover_parameters.json");
+    let air_public_input_json: PublicInput =
+        serde_json::from_str(&fs::read_to_string(air_public_input)?)
+            .map_err(ProverError::InvalidPublicInput)?;
 
+    // user-supplied files are checked here as Stone only reports opaque errors for them
     let prover_parameters_path = if let Some(parameter_file) = &parameter_file {
+        let parameters = load_parameter_file(
+            parameter_file,
+            air_public_input_json.n_steps,
+            air_public_input_json.log_cpu_rows_per_step(),
+        )?;
+        // the verifier cannot check proofs made with other hash settings
+        if let Some(target) = verifier_target {
+            parameters.validate_commitment_scheme(target, stone_version)?;
+        }
         parameter_file
     } else {
-        let air_public_input_json: PublicInput =
-            serde_json::from_str(&fs::read_to_string(air_public_input)?).unwrap();
         let prover_parameters =
-            ProverParametersConfig::new(air_public_input_json.n_steps, parameter_config).unwrap();
+            ProverParametersConfig::new(air_public_input_json.n_steps, parameter_config)
+                .map_err(ProverError::InvalidParameters)?;
         write_json_to_file(prover_parameters, &tmp_prover_parameters_path)?;
         &tmp_prover_parameters_path
     };
@@ -180,6 +221,7 @@ fn run_stone_prover_internal(
     let tmp_prover_config_path = tmp_dir.path().join("prover_config.json");
 
     let prover_config_path = if let Some(prover_config_file) = &prover_config_file {
+        load_prover_config_file(prover_config_file)?;
         prover_config_file
     } else {
         let prover_config = ProverConfig::new(prover_config).unwrap();
@@ -1,14 +1,132 @@
 use crate::{
+    args::{Preset, StoneVersion, VerifierTarget},
     define_enum,
-    fri::{DefaultFriComputer, FriComputer},
+    fri::{
+        split_fri_steps, DefaultFriComputer, FriComputer, ProofSizeFriComputer,
+        DEFAULT_MAX_FRI_STEP, MAX_FRI_STEP,
+    },
 };
-use clap::Args;
+use clap::{Args, ValueEnum};
 use serde::{Deserialize, Serialize};
 use serde_json::Result;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ValidationError {
+    #[error("Failed to read {path:?}: {source}")]
+    IO {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Invalid {kind} {path:?}: {source}")]
+    InvalidFile {
+        kind: &'static str,
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("{0} is missing from the prover parameters, Stone requires it")]
+    MissingParameter(&'static str),
+    #[error("{name} should be positive, got {value}")]
+    NotPositive { name: &'static str, value: i64 },
+    #[error("stark.fri.last_layer_degree_bound should be a power of two, got {0}")]
+    LastLayerDegreeBoundNotPowerOfTwo(u32),
+    #[error("stark.fri.last_layer_degree_bound {last_layer_degree_bound} is too large for a trace of {n_steps} steps, the maximum is {max}")]
+    LastLayerDegreeBoundTooLarge {
+        last_layer_degree_bound: u32,
+        n_steps: u32,
+        max: u64,
+    },
+    #[error("The trace length of {0} steps overflows")]
+    TooManySteps(u32),
+    #[error("stark.fri.fri_step_list {fri_step_list:?} sums to {actual} but {n_steps} steps with a last layer degree bound of {last_layer_degree_bound} require a sum of {expected}, e.g. {suggested:?}")]
+    FriDegreeMismatch {
+        n_steps: u32,
+        last_layer_degree_bound: u32,
+        fri_step_list: Vec<u32>,
+        actual: u32,
+        expected: u32,
+        suggested: Vec<u32>,
+    },
+    #[error("The hash settings of the prover parameters {actual:?} are not accepted by the {target} verifier for Stone {version} proofs, which accepts {expected:?}")]
+    CommitmentSchemeMismatch {
+        target: &'static str,
+        version: &'static str,
+        expected: Vec<CommitmentScheme>,
+        actual: CommitmentScheme,
+    },
+}
+
+/// Hash settings of the prover parameters that a verifier has to agree with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitmentScheme {
+    pub channel_hash: Hash,
+    pub commitment_hash: CommitmentHash,
+    pub pow_hash: PowHash,
+    pub n_verifier_friendly_commitment_layers: u32,
+    pub verifier_friendly_channel_updates: bool,
+    pub verifier_friendly_commitment_hash: Hash,
+}
+
+impl CommitmentScheme {
+    /// The hash settings Stone uses for parameters missing from the parameter file
+    pub fn stone_defaults() -> CommitmentScheme {
+        CommitmentScheme {
+            channel_hash: Hash::keccak256,
+            commitment_hash: CommitmentHash::keccak256_masked160_msb,
+            pow_hash: PowHash::keccak256,
+            n_verifier_friendly_commitment_layers: 0,
+            verifier_friendly_channel_updates: false,
+            verifier_friendly_commitment_hash: Hash::poseidon3,
+        }
+    }
+}
+
+/// Hash settings and STARK parameters recorded in the `proof_parameters` of a proof file
+///
+/// Unlike `ProverParametersConfig`, every key is optional and unknown keys are ignored, as Stone
+/// only writes the parameters that were set in the parameter file it was given.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ProofParameters {
+    pub channel_hash: Option<Hash>,
+    pub commitment_hash: Option<CommitmentHash>,
+    pub pow_hash: Option<PowHash>,
+    pub n_verifier_friendly_commitment_layers: Option<u32>,
+    pub verifier_friendly_channel_updates: Option<bool>,
+    pub verifier_friendly_commitment_hash: Option<Hash>,
+    pub stark: Option<StarkParameters>,
+}
+
+impl ProofParameters {
+    /// Returns the hash settings, with the Stone defaults for the settings that are not recorded
+    pub fn commitment_scheme(&self) -> CommitmentScheme {
+        let defaults = CommitmentScheme::stone_defaults();
+        CommitmentScheme {
+            channel_hash: self.channel_hash.clone().unwrap_or(defaults.channel_hash),
+            commitment_hash: self
+                .commitment_hash
+                .clone()
+                .unwrap_or(defaults.commitment_hash),
+            pow_hash: self.pow_hash.clone().unwrap_or(defaults.pow_hash),
+            n_verifier_friendly_commitment_layers: self
+                .n_verifier_friendly_commitment_layers
+                .unwrap_or(defaults.n_verifier_friendly_commitment_layers),
+            verifier_friendly_channel_updates: self
+                .verifier_friendly_channel_updates
+                .unwrap_or(defaults.verifier_friendly_channel_updates),
+            verifier_friendly_commitment_hash: self
+                .verifier_friendly_commitment_hash
+                .clone()
+                .unwrap_or(defaults.verifier_friendly_commitment_hash),
+        }
+    }
+}
 
 define_enum! {
     CommitmentHash,
     keccak256_masked160_lsb => "keccak256_masked160_lsb",
+    keccak256_masked160_msb => "keccak256_masked160_msb",
     blake256_masked248_lsb => "blake256_masked248_lsb",
 }
 
@@ -31,13 +149,21 @@ define_enum! {
     keccak256 => "keccak256",
 }
 
-#[derive(Args, Serialize, Deserialize, Debug, Clone)]
+define_enum! {
+    FriStrategy,
+    prover_time => "prover_time",
+    proof_size => "proof_size",
+}
+
+#[derive(Args, Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
 pub struct StatementParameters {
     #[clap(long = "page_hash", default_value = "pedersen", value_enum)]
     page_hash: Option<PageHash>,
 }
 
 #[derive(Args, Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct StarkParameters {
     #[clap(flatten)]
     pub fri: FriParameters,
@@ -46,6 +172,7 @@ pub struct StarkParameters {
 }
 
 #[derive(Args, Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct FriParameters {
     #[clap(long = "fri_step_list", num_args = 1.., value_delimiter = ' ', help = "autogenerated if not explicitly provided")]
     pub fri_step_list: Option<Vec<u32>>,
@@ -58,6 +185,7 @@ pub struct FriParameters {
 }
 
 #[derive(Args, Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct ProverParametersConfig {
     #[clap(long = "field", default_value = "PrimeField0")]
     field: Option<String>,
@@ -74,6 +202,7 @@ pub struct ProverParametersConfig {
     #[clap(long = "pow_hash", default_value = "keccak256", value_enum)]
     pow_hash: Option<PowHash>,
     #[clap(flatten)]
+    #[serde(default)]
     statement: StatementParameters,
     #[clap(flatten)]
     stark: StarkParameters,
@@ -87,6 +216,22 @@ pub struct ProverParametersConfig {
         value_enum
     )]
     verifier_friendly_commitment_hash: Option<Hash>,
+    #[clap(
+        long = "fri_strategy",
+        default_value = "prover-time",
+        value_enum,
+        help = "How the FRI step list is derived when not explicitly provided"
+    )]
+    #[serde(skip)]
+    fri_strategy: Option<FriStrategy>,
+    #[clap(
+        long = "fri_max_step",
+        default_value = "4",
+        value_parser = clap::value_parser!(u32).range(1..=MAX_FRI_STEP as i64),
+        help = "Largest FRI step used when the FRI step list is derived"
+    )]
+    #[serde(skip)]
+    fri_max_step: Option<u32>,
 }
 
 impl ProverParametersConfig {
@@ -94,13 +239,15 @@ impl ProverParametersConfig {
         nb_steps: u32,
         parameter_config: &ProverParametersConfig,
     ) -> Result<ProverParametersConfig> {
-        let computed_fri_parameters = DefaultFriComputer.compute_fri_parameters(nb_steps);
-        let computed_fri_step_list = parameter_config
-            .stark
-            .fri
-            .fri_step_list
-            .clone()
-            .unwrap_or(computed_fri_parameters.fri_step_list.unwrap());
+        let max_step_value = parameter_config
+            .fri_max_step
+            .unwrap_or(DEFAULT_MAX_FRI_STEP);
+        let fri_computer: &dyn FriComputer = match parameter_config.fri_strategy {
+            Some(FriStrategy::proof_size) => &ProofSizeFriComputer,
+            Some(FriStrategy::prover_time) | None => &DefaultFriComputer,
+        };
+        let computed_fri_parameters =
+            fri_computer.compute_fri_parameters(nb_steps, &parameter_config.stark, max_step_value);
 
         let prover_parameters = ProverParametersConfig {
             field: parameter_config.field.clone(),
@@ -113,12 +260,7 @@ impl ProverParametersConfig {
                 page_hash: parameter_config.statement.page_hash.clone(),
             },
             stark: StarkParameters {
-                fri: FriParameters {
-                    fri_step_list: Some(computed_fri_step_list),
-                    last_layer_degree_bound: parameter_config.stark.fri.last_layer_degree_bound,
-                    n_queries: parameter_config.stark.fri.n_queries,
-                    proof_of_work_bits: parameter_config.stark.fri.proof_of_work_bits,
-                },
+                fri: computed_fri_parameters,
                 log_n_cosets: parameter_config.stark.log_n_cosets,
             },
             use_extension_field: parameter_config.use_extension_field,
@@ -126,12 +268,187 @@ impl ProverParametersConfig {
             verifier_friendly_commitment_hash: parameter_config
                 .verifier_friendly_commitment_hash
                 .clone(),
+            fri_strategy: parameter_config.fri_strategy.clone(),
+            fri_max_step: parameter_config.fri_max_step,
         };
 
         Ok(prover_parameters)
     }
 }
 
+impl ProverParametersConfig {
+    /// Checks that the parameters can be used by Stone to prove a trace of `n_steps` steps
+    ///
+    /// The FRI degree equation `log2(n_steps) + 4 = log2(last_layer_degree_bound) + sum(fri_step_list)`
+    /// must hold, where 4 is the log of the number of trace rows per Cairo step.
+    ///
+    /// # Arguments
+    ///
+    /// * `n_steps` - The number of steps of the trace, as found in the public input
+    /// * `log_cpu_rows_per_step` - log₂ of the number of trace rows of a step of the layout
+    ///
+    /// # Returns
+    ///
+    /// An empty `Result` if the parameters are valid, or the first `ValidationError` found
+    pub fn validate(
+        &self,
+        n_steps: u32,
+        log_cpu_rows_per_step: u32,
+    ) -> std::result::Result<(), ValidationError> {
+        let fri = &self.stark.fri;
+        let fri_step_list = fri
+            .fri_step_list
+            .as_ref()
+            .ok_or(ValidationError::MissingParameter("stark.fri.fri_step_list"))?;
+        let last_layer_degree_bound =
+            fri.last_layer_degree_bound
+                .ok_or(ValidationError::MissingParameter(
+                    "stark.fri.last_layer_degree_bound",
+                ))?;
+        let n_queries = fri
+            .n_queries
+            .ok_or(ValidationError::MissingParameter("stark.fri.n_queries"))?;
+        fri.proof_of_work_bits
+            .ok_or(ValidationError::MissingParameter(
+                "stark.fri.proof_of_work_bits",
+            ))?;
+        let log_n_cosets = self
+            .stark
+            .log_n_cosets
+            .ok_or(ValidationError::MissingParameter("stark.log_n_cosets"))?;
+
+        for (name, value) in [
+            ("stark.fri.n_queries", n_queries as i64),
+            ("stark.log_n_cosets", log_n_cosets as i64),
+        ] {
+            if value <= 0 {
+                return Err(ValidationError::NotPositive { name, value });
+            }
+        }
+
+        if !last_layer_degree_bound.is_power_of_two() {
+            return Err(ValidationError::LastLayerDegreeBoundNotPowerOfTwo(
+                last_layer_degree_bound,
+            ));
+        }
+        let log_trace_length = n_steps
+            .checked_next_power_of_two()
+            .ok_or(ValidationError::TooManySteps(n_steps))?
+            .ilog2()
+            + log_cpu_rows_per_step;
+        let log_last_layer_degree_bound = last_layer_degree_bound.ilog2();
+        if log_last_layer_degree_bound > log_trace_length {
+            return Err(ValidationError::LastLayerDegreeBoundTooLarge {
+                last_layer_degree_bound,
+                n_steps,
+                max: 1u64.checked_shl(log_trace_length).unwrap_or(u64::MAX),
+            });
+        }
+        let expected = log_trace_length - log_last_layer_degree_bound;
+        let actual = fri_step_list.iter().sum::<u32>();
+        if actual != expected {
+            return Err(ValidationError::FriDegreeMismatch {
+                n_steps,
+                last_layer_degree_bound,
+                fri_step_list: fri_step_list.clone(),
+                actual,
+                expected,
+                suggested: std::iter::once(0)
+                    .chain(split_fri_steps(expected, DEFAULT_MAX_FRI_STEP))
+                    .collect(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that the hash settings are accepted by `target` for proofs of `stone_version`
+    ///
+    /// The accepted hash settings are the ones of the presets of the target for that Stone
+    /// version, so hashes the verifier only supports for one version are rejected for the other.
+    /// The Integrity verifiers read the number of verifier friendly commitment layers from the
+    /// proof, so it is not compared for them, and the local Stone verifier accepts any hashes.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The verifier the proof is meant for
+    /// * `stone_version` - The Stone version used to create the proof
+    ///
+    /// # Returns
+    ///
+    /// An empty `Result` if the hash settings are accepted, or a `ValidationError` otherwise
+    pub fn validate_commitment_scheme(
+        &self,
+        target: &VerifierTarget,
+        stone_version: &StoneVersion,
+    ) -> std::result::Result<(), ValidationError> {
+        if *target == VerifierTarget::stone {
+            return Ok(());
+        }
+        let actual = self.commitment_scheme();
+        let expected = Preset::value_variants()
+            .iter()
+            .filter(|preset| {
+                preset.verifier_target() == *target && preset.stone_version() == *stone_version
+            })
+            .map(|preset| preset.commitment_scheme())
+            .collect::<Vec<_>>();
+        let accepted = expected.iter().any(|expected| match target {
+            VerifierTarget::integrity => {
+                actual
+                    == CommitmentScheme {
+                        n_verifier_friendly_commitment_layers: actual
+                            .n_verifier_friendly_commitment_layers,
+                        ..expected.clone()
+                    }
+            }
+            _ => actual == *expected,
+        });
+        if !accepted {
+            return Err(ValidationError::CommitmentSchemeMismatch {
+                target: target.clone().to_str(),
+                version: stone_version.clone().to_str(),
+                expected,
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the config with its STARK parameters replaced by `stark`
+    pub fn with_stark_parameters(self, stark: StarkParameters) -> ProverParametersConfig {
+        ProverParametersConfig { stark, ..self }
+    }
+
+    /// Returns the hash settings Stone uses with these parameters
+    pub fn commitment_scheme(&self) -> CommitmentScheme {
+        ProofParameters {
+            channel_hash: self.channel_hash.clone(),
+            commitment_hash: self.commitment_hash.clone(),
+            pow_hash: self.pow_hash.clone(),
+            n_verifier_friendly_commitment_layers: self.n_verifier_friendly_commitment_layers,
+            verifier_friendly_channel_updates: self.verifier_friendly_channel_updates,
+            verifier_friendly_commitment_hash: self.verifier_friendly_commitment_hash.clone(),
+            stark: Some(self.stark.clone()),
+        }
+        .commitment_scheme()
+    }
+
+    /// Returns the config with its hash settings replaced by `scheme`
+    pub fn with_commitment_scheme(self, scheme: CommitmentScheme) -> ProverParametersConfig {
+        ProverParametersConfig {
+            channel_hash: Some(scheme.channel_hash),
+            commitment_hash: Some(scheme.commitment_hash),
+            pow_hash: Some(scheme.pow_hash),
+            n_verifier_friendly_commitment_layers: Some(
+                scheme.n_verifier_friendly_commitment_layers,
+            ),
+            verifier_friendly_channel_updates: Some(scheme.verifier_friendly_channel_updates),
+            verifier_friendly_commitment_hash: Some(scheme.verifier_friendly_commitment_hash),
+            ..self
+        }
+    }
+}
+
 impl Default for ProverParametersConfig {
     fn default() -> Self {
         ProverParametersConfig {
@@ -155,11 +472,14 @@ impl Default for ProverParametersConfig {
             use_extension_field: Some(false),
             verifier_friendly_channel_updates: Some(true),
             verifier_friendly_commitment_hash: Some(Hash::poseidon3),
+            fri_strategy: Some(FriStrategy::prover_time),
+            fri_max_step: Some(DEFAULT_MAX_FRI_STEP),
         }
     }
 }
 
 #[derive(Args, Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct CachedLdeConfig {
     #[clap(long = "store_full_lde", default_value = "false")]
     pub store_full_lde: Option<bool>,
@@ -168,6 +488,7 @@ pub struct CachedLdeConfig {
 }
 
 #[derive(Args, Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct ProverConfig {
     #[clap(flatten)]
     pub cached_lde_config: CachedLdeConfig,
@@ -193,6 +514,90 @@ impl ProverConfig {
     }
 }
 
+impl ProverConfig {
+    /// Checks that the task sizes of the prover config are usable by Stone
+    pub fn validate(&self) -> std::result::Result<(), ValidationError> {
+        for (name, value) in [
+            (
+                "constraint_polynomial_task_size",
+                self.constraint_polynomial_task_size,
+            ),
+            (
+                "table_prover_n_tasks_per_segment",
+                self.table_prover_n_tasks_per_segment,
+            ),
+        ] {
+            if let Some(value) = value.filter(|value| *value <= 0) {
+                return Err(ValidationError::NotPositive {
+                    name,
+                    value: value as i64,
+                });
+            }
+        }
+        if let Some(value) = self
+            .n_out_of_memory_merkle_layers
+            .filter(|value| *value < 0)
+        {
+            return Err(ValidationError::NotPositive {
+                name: "n_out_of_memory_merkle_layers",
+                value: value as i64,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Reads a file given with `--parameter_file` and validates it for a trace of `n_steps` steps
+///
+/// # Arguments
+///
+/// * `path` - Path to the prover parameters file
+/// * `n_steps` - The number of steps of the trace, as found in the public input
+/// * `log_cpu_rows_per_step` - log₂ of the number of trace rows of a step of the layout
+///
+/// # Returns
+///
+/// A `Result` containing the parsed `ProverParametersConfig`, or a `ValidationError` describing the first problem
+pub fn load_parameter_file(
+    path: &Path,
+    n_steps: u32,
+    log_cpu_rows_per_step: u32,
+) -> std::result::Result<ProverParametersConfig, ValidationError> {
+    let parameters: ProverParametersConfig = read_config_file(path, "prover parameters file")?;
+    parameters.validate(n_steps, log_cpu_rows_per_step)?;
+    Ok(parameters)
+}
+
+/// Reads a file given with `--prover_config_file` and validates it
+///
+/// # Arguments
+///
+/// * `path` - Path to the prover config file
+///
+/// # Returns
+///
+/// A `Result` containing the parsed `ProverConfig`, or a `ValidationError` describing the first problem
+pub fn load_prover_config_file(path: &Path) -> std::result::Result<ProverConfig, ValidationError> {
+    let config: ProverConfig = read_config_file(path, "prover config file")?;
+    config.validate()?;
+    Ok(config)
+}
+
+fn read_config_file<T: serde::de::DeserializeOwned>(
+    path: &Path,
+    kind: &'static str,
+) -> std::result::Result<T, ValidationError> {
+    let content = std::fs::read_to_string(path).map_err(|source| ValidationError::IO {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    serde_json::from_str(&content).map_err(|source| ValidationError::InvalidFile {
+        kind,
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
 impl Default for ProverConfig {
     fn default() -> Self {
         ProverConfig {
@@ -206,3 +611,120 @@ impl Default for ProverConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fri::LOG_CPU_ROWS_PER_STEP;
+    use rstest::rstest;
+
+    fn parameters(fri_step_list: Vec<u32>, last_layer_degree_bound: u32) -> ProverParametersConfig {
+        let mut parameters = ProverParametersConfig::default();
+        parameters.stark.fri.fri_step_list = Some(fri_step_list);
+        parameters.stark.fri.last_layer_degree_bound = Some(last_layer_degree_bound);
+        parameters
+    }
+
+    #[rstest]
+    #[case(parameters(vec![0, 4, 4, 3], 64), 8192, LOG_CPU_ROWS_PER_STEP, true)]
+    #[case(parameters(vec![0, 4, 4, 3], 64), 6000, LOG_CPU_ROWS_PER_STEP, true)]
+    #[case(parameters(vec![0, 4, 4, 4], 64), 8192, LOG_CPU_ROWS_PER_STEP, false)]
+    #[case(parameters(vec![0, 4, 4, 3], 48), 8192, LOG_CPU_ROWS_PER_STEP, false)]
+    #[case(parameters(vec![0], 1 << 18), 8192, LOG_CPU_ROWS_PER_STEP, false)]
+    #[case(parameters(vec![0, 4, 4, 3], 64), u32::MAX, LOG_CPU_ROWS_PER_STEP, false)]
+    // a dynamic layout with a cpu_component_step of 8
+    #[case(parameters(vec![0, 4, 4, 4, 2], 64), 8192, LOG_CPU_ROWS_PER_STEP + 3, true)]
+    #[case(parameters(vec![0, 4, 4, 3], 64), 8192, LOG_CPU_ROWS_PER_STEP + 3, false)]
+    fn test_validate_fri_parameters(
+        #[case] parameters: ProverParametersConfig,
+        #[case] n_steps: u32,
+        #[case] log_cpu_rows_per_step: u32,
+        #[case] expected_ok: bool,
+    ) {
+        let result = parameters.validate(n_steps, log_cpu_rows_per_step);
+        assert_eq!(result.is_ok(), expected_ok);
+    }
+
+    #[rstest]
+    #[case(
+        ProverParametersConfig::default(),
+        VerifierTarget::integrity,
+        StoneVersion::V5,
+        true
+    )]
+    #[case(
+        ProverParametersConfig::default(),
+        VerifierTarget::integrity,
+        StoneVersion::V6,
+        true
+    )]
+    #[case(
+        ProverParametersConfig::default(),
+        VerifierTarget::ethereum,
+        StoneVersion::V5,
+        false
+    )]
+    #[case(
+        ProverParametersConfig::default(),
+        VerifierTarget::stone,
+        StoneVersion::V5,
+        true
+    )]
+    #[case(stone_defaults(), VerifierTarget::ethereum, StoneVersion::V5, true)]
+    // the GPS verifier only accepts Stone v5 proofs
+    #[case(stone_defaults(), VerifierTarget::ethereum, StoneVersion::V6, false)]
+    #[case(stone_defaults(), VerifierTarget::integrity, StoneVersion::V6, false)]
+    #[case(stone_defaults(), VerifierTarget::stone, StoneVersion::V6, true)]
+    fn test_validate_commitment_scheme(
+        #[case] parameters: ProverParametersConfig,
+        #[case] target: VerifierTarget,
+        #[case] stone_version: StoneVersion,
+        #[case] expected_ok: bool,
+    ) {
+        let result = parameters.validate_commitment_scheme(&target, &stone_version);
+        assert_eq!(result.is_ok(), expected_ok);
+    }
+
+    fn stone_defaults() -> ProverParametersConfig {
+        ProverParametersConfig::default().with_commitment_scheme(CommitmentScheme::stone_defaults())
+    }
+
+    #[test]
+    fn test_parse_without_statement() {
+        let parameters = serde_json::from_str::<ProverParametersConfig>(
+            r#"{"field": "PrimeField0", "stark": {"fri": {"n_queries": 18}, "log_n_cosets": 4}}"#,
+        )
+        .unwrap();
+        assert!(parameters.statement.page_hash.is_none());
+        assert_eq!(parameters.stark.fri.n_queries, Some(18));
+    }
+
+    #[test]
+    fn test_parse_unknown_field() {
+        let result = serde_json::from_str::<ProverParametersConfig>(
+            r#"{"stark": {"fri": {"fri_steps_list": [0, 4]}}}"#,
+        );
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("unknown field `fri_steps_list`"));
+    }
+
+    #[derive(clap::Parser)]
+    struct ParametersCli {
+        #[clap(flatten)]
+        parameters: ProverParametersConfig,
+    }
+
+    #[rstest]
+    #[case("1", true)]
+    #[case("16", true)]
+    #[case("0", false)]
+    #[case("17", false)]
+    #[case("32", false)]
+    fn test_parse_fri_max_step(#[case] fri_max_step: &str, #[case] expected_ok: bool) {
+        use clap::Parser;
+        let result = ParametersCli::try_parse_from(["stone-cli", "--fri_max_step", fri_max_step]);
+        assert_eq!(result.is_ok(), expected_ok);
+    }
+}
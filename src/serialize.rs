@@ -1,41 +1,33 @@
+mod calldata;
+pub mod decode;
+mod evm;
+pub mod report;
+mod split;
 mod vec252;
 
-use crate::args::{LayoutName, SerializationType};
+use crate::args::{CairoVersion, CalldataFormat, LayoutName, SerializationType};
 use crate::args::{Network, SerializeArgs};
+use crate::compat::integrity_verifier_config;
+use crate::utils::write_json_to_file;
 use anyhow::Result;
-use cairo_felt::Felt252;
+use calldata::StarknetCall;
 use itertools::chain;
 use itertools::Itertools;
-use num_traits::Num;
+use rand::Rng;
+use report::CalldataReport;
 use starknet_crypto::Felt;
 use std::fs::write;
 use std::io::BufRead;
 use std::path::Path;
 use std::path::PathBuf;
+use std::str::FromStr;
 use swiftness::transform::{Expr, StarkProofExprs};
 use swiftness::transform_stark::TransformTo;
 use swiftness_air::layout::*;
-use swiftness_fri::{CONST_STATE, VAR_STATE, WITNESS};
 use swiftness_proof_parser::parse;
-use swiftness_stark::stark;
 use thiserror::Error;
 use vec252::VecFelt252;
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub enum CairoVersion {
-    Cairo0 = 0,
-    Cairo1 = 1,
-}
-
-impl From<CairoVersion> for Felt252 {
-    fn from(value: CairoVersion) -> Self {
-        match value {
-            CairoVersion::Cairo0 => Felt252::from(0),
-            CairoVersion::Cairo1 => Felt252::from(1),
-        }
-    }
-}
-
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("Failed to interact with the file system")]
@@ -49,123 +41,354 @@ pub enum Error {
     #[error("Extra output file is required for serializing proofs for Ethereum")]
     ExtraOutputFileNotSpecified,
     #[error("Failed to verify proof: {0}")]
-    Verify(#[from] stark::Error),
+    Verify(String),
     #[error("Serialization is not supported for the {0} layout")]
     UnsupportedLayout(LayoutName),
     #[error("Serialization type is not specified")]
     SerializationTypeNotSpecified,
+    #[error("Fact topologies file is required for splitting proofs for Ethereum")]
+    FactTopologiesFileNotSpecified,
+    #[error("Output directory is required for splitting proofs for Ethereum")]
+    OutputDirNotSpecified,
+    #[error("Failed to split the proof for the Ethereum verifier: {0}")]
+    EvmAdapter(String),
+    #[error("Invalid felt in the serialized proof: {0}")]
+    InvalidFelt(String),
+    #[error(transparent)]
+    Compat(#[from] crate::compat::Error),
+    #[error("The proof uses the {proof} layout but --layout is {given}")]
+    LayoutMismatch {
+        proof: LayoutName,
+        given: LayoutName,
+    },
+    #[error("Failed to decode the calldata: {0}")]
+    Decode(String),
+    #[error("The calldata does not encode the {0} of the proof")]
+    CheckFailed(&'static str),
+    #[error("The report of proofs serialized for Ethereum requires --split")]
+    ReportRequiresSplit,
 }
 
-pub fn serialize_proof(args: SerializeArgs) -> Result<(), Error> {
+/// Serializes a proof for the verifiers of `args.network`
+///
+/// # Arguments
+///
+/// * `args` - Arguments for serializing the proof
+///
+/// # Returns
+///
+/// A `Result` containing the `CalldataReport` if `args.report` is set, or an `Error` on failure
+pub fn serialize_proof(args: SerializeArgs) -> Result<Option<CalldataReport>, Error> {
     let proof_file = args.proof.clone();
+    let mut calldata_report = None;
     match args.network {
         Network::ethereum => {
+            if args.report && !args.split {
+                return Err(Error::ReportRequiresSplit);
+            }
             let proof_with_annotations_json = parse_bootloader_proof_file(
                 &proof_file,
                 args.annotation_file,
                 args.extra_output_file,
             )?;
 
+            if args.split {
+                let output_dir = args.output_dir.ok_or(Error::OutputDirNotSpecified)?;
+                let fact_topologies_file = args
+                    .fact_topologies_file
+                    .ok_or(Error::FactTopologiesFileNotSpecified)?;
+                let fact_topologies = read_fact_topologies(&fact_topologies_file)?;
+                let transactions =
+                    evm::split_proof_calldata(&proof_with_annotations_json, fact_topologies)?;
+
+                std::fs::create_dir_all(&output_dir)?;
+                let files: Vec<String> = transactions
+                    .iter()
+                    .enumerate()
+                    .map(|(i, transaction)| format!("{:03}_{}.calldata", i, transaction.contract))
+                    .collect();
+                for (file, transaction) in files.iter().zip(&transactions) {
+                    write(output_dir.join(file), transaction.calldata.to_string())?;
+                }
+                if args.report {
+                    let input = std::fs::read_to_string(&proof_file)?;
+                    calldata_report = Some(report::ethereum_report(&transactions, &files, &input)?);
+                }
+            }
+
             std::fs::write(args.output.clone().unwrap(), proof_with_annotations_json).unwrap();
         }
-        Network::starknet => match args.serialization_type {
-            Some(SerializationType::monolith) => {
-                let output = args.output.clone().unwrap();
-                let input = std::fs::read_to_string(proof_file.clone())?;
-                let stark_proof: StarkProofExprs = parse(input)?.into();
-                let config: VecFelt252 =
-                    serde_json::from_str(&stark_proof.config.to_string()).unwrap();
-                let public_input: VecFelt252 =
-                    serde_json::from_str(&stark_proof.public_input.to_string()).unwrap();
-                let unsent_commitment: VecFelt252 =
-                    serde_json::from_str(&stark_proof.unsent_commitment.to_string()).unwrap();
-                let witness: VecFelt252 =
-                    serde_json::from_str(&stark_proof.witness.to_string()).unwrap();
-
-                let proof = chain!(
-                    config.into_iter(),
-                    public_input.into_iter(),
-                    unsent_commitment.into_iter(),
-                    witness.into_iter()
-                );
-
-                let calldata_string = proof
-                    .into_iter()
-                    .map(|f| f.to_string())
-                    .collect::<Vec<String>>()
-                    .join(" ");
-
-                std::fs::write(output, calldata_string)?;
+        Network::starknet => {
+            let serialization_type = args
+                .serialization_type
+                .clone()
+                .ok_or(Error::SerializationTypeNotSpecified)?;
+            let input = std::fs::read_to_string(proof_file.clone())?;
+            let verifier_config = integrity_verifier_config(
+                &proof_file,
+                serialization_type.clone(),
+                &args.stone_version,
+                &args.cairo_version.clone().unwrap_or(CairoVersion::cairo0),
+                &args.memory_verification,
+            )?;
+            let header = calldata::verifier_config_calldata(&verifier_config);
+            let layout = LayoutName::from_str(&verifier_config.layout)
+                .expect("Integrity verifier configs use Stone layout names");
+            if let Some(given) = &args.layout {
+                if *given != layout {
+                    return Err(Error::LayoutMismatch {
+                        proof: layout,
+                        given: given.clone(),
+                    });
+                }
             }
-            Some(SerializationType::split) => {
-                let output_dir = args.output_dir.clone().unwrap();
-                let layout = args.layout.unwrap();
-                let input = std::fs::read_to_string(proof_file.clone())?;
-                let stark_proof = parse(input.clone())?.transform_to();
-                let security_bits = stark_proof.config.security_bits();
-
-                match layout {
-                    LayoutName::dex => {
-                        stark_proof.verify::<dex::Layout>(security_bits)?;
-                    }
-                    LayoutName::recursive => {
-                        stark_proof.verify::<recursive::Layout>(security_bits)?;
-                    }
-                    LayoutName::recursive_with_poseidon => {
-                        stark_proof.verify::<recursive_with_poseidon::Layout>(security_bits)?;
-                    }
-                    LayoutName::small => {
-                        stark_proof.verify::<small::Layout>(security_bits)?;
-                    }
-                    LayoutName::starknet => {
-                        stark_proof.verify::<starknet::Layout>(security_bits)?;
-                    }
-                    LayoutName::starknet_with_keccak => {
-                        stark_proof.verify::<starknet_with_keccak::Layout>(security_bits)?;
-                    }
-                    layout @ (LayoutName::plain
-                    | LayoutName::recursive_large_output
-                    | LayoutName::all_solidity
-                    | LayoutName::all_cairo
-                    | LayoutName::automatic
-                    | LayoutName::dynamic) => {
-                        return Err(Error::UnsupportedLayout(layout));
-                    }
+
+            let (calls, job_id, manifest_file) = match serialization_type {
+                SerializationType::monolith => {
+                    let output = args.output.clone().unwrap();
+                    let calls = vec![StarknetCall::new(
+                        output,
+                        calldata::VERIFY_PROOF_FULL,
+                        [
+                            header.clone(),
+                            parse_felts(monolith_calldata(input.clone())?.iter())?,
+                        ]
+                        .concat(),
+                    )];
+                    (calls, None, args.manifest.clone())
+                }
+                SerializationType::split => {
+                    let output_dir = args.output_dir.clone().unwrap();
+                    let calls = split_calls(
+                        split_proof(&input, layout.clone())?,
+                        &output_dir,
+                        header.clone(),
+                    );
+                    std::fs::create_dir_all(&output_dir)?;
+                    let job_id = args
+                        .job_id
+                        .unwrap_or_else(|| rand::thread_rng().gen_range(1..10_000_000_000_000));
+                    let manifest_file = args
+                        .manifest
+                        .clone()
+                        .unwrap_or_else(|| output_dir.join(calldata::MANIFEST_FILE));
+                    (calls, Some(Felt::from(job_id)), Some(manifest_file))
                 }
+            };
 
-                #[allow(static_mut_refs)]
-                let (const_state, mut var_state, mut witness) =
-                    unsafe { (CONST_STATE.clone(), VAR_STATE.clone(), WITNESS.clone()) };
-                let initial = serialize(input)?
-                    .split_whitespace()
-                    .map(|s| Felt::from_dec_str(s).unwrap().to_hex_string())
-                    .join(" ");
-
-                let final_ = format!(
-                    "{} {} {}",
-                    const_state,
-                    var_state.pop().unwrap(),
-                    witness.pop().unwrap()
-                );
+            let format = args.format.clone().unwrap_or(match serialization_type {
+                SerializationType::monolith => CalldataFormat::decimal,
+                SerializationType::split => CalldataFormat::hex,
+            });
+            let manifest = calldata::write_calls(
+                &calls,
+                serialization_type.clone(),
+                format,
+                job_id,
+                verifier_config,
+            )?;
+            if args.check {
+                check_calldata(
+                    &input,
+                    &calls,
+                    &header,
+                    &layout,
+                    &serialization_type,
+                    job_id.is_some(),
+                    &manifest.format,
+                )?;
+            }
+            if args.report {
+                calldata_report = Some(report::starknet_report(
+                    &calls,
+                    &manifest,
+                    job_id.is_some(),
+                    &input,
+                )?);
+            }
+            if let Some(manifest_file) = manifest_file {
+                write_json_to_file(manifest, manifest_file)?;
+            }
+        }
+    }
+    Ok(calldata_report)
+}
 
-                std::fs::create_dir_all(&output_dir)?;
+/// A proof serialized for the split Integrity verifier, as the calldata of each of its calls
+///
+/// The calldata does not include the job id, nor the verifier config of the initial call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitProof {
+    /// Calldata of `verify_proof_initial`
+    pub initial: Vec<Felt>,
+    /// Calldata of each `verify_proof_step` call, in submission order
+    pub steps: Vec<Vec<Felt>>,
+    /// Calldata of `verify_proof_final_and_register_fact`
+    pub final_: Vec<Felt>,
+}
 
-                write(output_dir.join("initial"), initial)?;
-                write(output_dir.join("final"), final_)?;
+/// Serializes a proof for the split Integrity verifier
+///
+/// The proof is verified with the Cairo verifier of the layout, which gives the states passed
+/// between the calls. The states are computed for each call, so proofs can be split from
+/// several threads at once.
+///
+/// # Arguments
+///
+/// * `input` - The content of the proof file
+/// * `layout` - The layout of the proof, which selects the AIR used to verify it
+///
+/// # Returns
+///
+/// A `Result` containing the `SplitProof`, or an `Error` if the proof cannot be verified
+pub fn split_proof(input: &str, layout: LayoutName) -> Result<SplitProof, Error> {
+    let stark_proof = parse(input.to_string())?.transform_to();
+    let security_bits = stark_proof.config.security_bits();
 
-                for (i, (v, w)) in var_state.iter().zip(witness.iter()).enumerate() {
-                    write(
-                        output_dir.join(format!("step{}", i + 1)),
-                        format!("{} {} {}", const_state, v, w),
-                    )?;
-                }
-            }
-            None => return Err(Error::SerializationTypeNotSpecified),
-        },
+    let (steps, final_) = match layout {
+        LayoutName::dex => split::fri_calls::<dex::Layout>(&stark_proof, security_bits)?,
+        LayoutName::recursive => {
+            split::fri_calls::<recursive::Layout>(&stark_proof, security_bits)?
+        }
+        LayoutName::recursive_with_poseidon => {
+            split::fri_calls::<recursive_with_poseidon::Layout>(&stark_proof, security_bits)?
+        }
+        LayoutName::small => split::fri_calls::<small::Layout>(&stark_proof, security_bits)?,
+        LayoutName::starknet => split::fri_calls::<starknet::Layout>(&stark_proof, security_bits)?,
+        LayoutName::starknet_with_keccak => {
+            split::fri_calls::<starknet_with_keccak::Layout>(&stark_proof, security_bits)?
+        }
+        layout @ (LayoutName::plain
+        | LayoutName::recursive_large_output
+        | LayoutName::all_solidity
+        | LayoutName::all_cairo
+        | LayoutName::automatic
+        | LayoutName::dynamic) => {
+            return Err(Error::UnsupportedLayout(layout));
+        }
+    };
+    Ok(SplitProof {
+        initial: parse_felts(serialize(input.to_string())?.split_whitespace())?,
+        steps,
+        final_,
+    })
+}
+
+/// Writes the files of a split proof and returns their calls in submission order
+///
+/// # Arguments
+///
+/// * `split_proof` - The split proof
+/// * `output_dir` - The directory the calldata files are written to
+/// * `header` - The verifier config, included before the proof in the initial call
+fn split_calls(split_proof: SplitProof, output_dir: &Path, header: Vec<Felt>) -> Vec<StarknetCall> {
+    let mut calls = vec![StarknetCall::new(
+        output_dir.join("initial"),
+        calldata::VERIFY_PROOF_INITIAL,
+        [header, split_proof.initial].concat(),
+    )];
+    for (i, step) in split_proof.steps.into_iter().enumerate() {
+        calls.push(StarknetCall::new(
+            output_dir.join(format!("step{}", i + 1)),
+            calldata::VERIFY_PROOF_STEP,
+            step,
+        ));
+    }
+    calls.push(StarknetCall::new(
+        output_dir.join("final"),
+        calldata::VERIFY_PROOF_FINAL,
+        split_proof.final_,
+    ));
+    calls
+}
+
+/// Decodes the calldata written for the calls of a proof and checks that it encodes the proof
+///
+/// The first call is rebuilt into the config, public input, unsent commitment and witness of the
+/// proof. The step and final calls of split proofs are rebuilt into their FRI states and layers,
+/// and the FRI witness they carry is compared with the parsed proof.
+///
+/// # Arguments
+///
+/// * `input` - The content of the proof file
+/// * `calls` - The calls, in submission order
+/// * `header` - The verifier config expected at the start of the calldata of the first call
+/// * `layout` - The layout of the proof
+/// * `serialization_type` - Whether the proof is verified in a single transaction or split
+/// * `has_job_id` - Whether a job id was given for the calls
+/// * `format` - The format the calldata was written in
+fn check_calldata(
+    input: &str,
+    calls: &[StarknetCall],
+    header: &[Felt],
+    layout: &LayoutName,
+    serialization_type: &SerializationType,
+    has_job_id: bool,
+    format: &CalldataFormat,
+) -> Result<(), Error> {
+    let read_call = |call: &StarknetCall| -> Result<Vec<Felt>, Error> {
+        let felts = calldata::read_calldata(&std::fs::read_to_string(&call.path)?)?;
+        // only the sncast format includes the job id in the files
+        let skipped = match format {
+            CalldataFormat::sncast if has_job_id && call.takes_job_id() => 1,
+            _ => 0,
+        };
+        Ok(felts.get(skipped..).unwrap_or_default().to_vec())
+    };
+    let (first, rest) = calls
+        .split_first()
+        .expect("a proof is serialized to at least one call");
+
+    let felts = read_call(first)?;
+    if felts.get(..header.len()) != Some(header) {
+        return Err(Error::CheckFailed("verifier config"));
+    }
+    // the FRI witness of split proofs is sent in the step calls
+    let with_fri_witness = *serialization_type == SerializationType::monolith;
+    let decoded = decode::decode_proof(&felts[header.len()..], layout, with_fri_witness)?;
+    decode::compare(&decoded, &decode::expected_proof(input, with_fri_witness)?)?;
+
+    if !rest.is_empty() {
+        let fri_calls = rest.iter().map(read_call).collect::<Result<Vec<_>, _>>()?;
+        decode::check_fri_calls(input, &fri_calls)?;
     }
     Ok(())
 }
 
+fn parse_felts<S: AsRef<str>>(values: impl Iterator<Item = S>) -> Result<Vec<Felt>, Error> {
+    values
+        .map(|value| calldata::parse_felt(value.as_ref()))
+        .collect()
+}
+
+/// Serializes a proof to the calldata of a single Integrity verification transaction
+///
+/// # Arguments
+///
+/// * `input` - The content of the proof file
+///
+/// # Returns
+///
+/// A `Result` containing the calldata as decimal strings, or an `Error` if the proof cannot be parsed
+pub fn monolith_calldata(input: String) -> Result<Vec<String>, Error> {
+    let stark_proof: StarkProofExprs = parse(input)?.into();
+    let config: VecFelt252 = serde_json::from_str(&stark_proof.config.to_string()).unwrap();
+    let public_input: VecFelt252 =
+        serde_json::from_str(&stark_proof.public_input.to_string()).unwrap();
+    let unsent_commitment: VecFelt252 =
+        serde_json::from_str(&stark_proof.unsent_commitment.to_string()).unwrap();
+    let witness: VecFelt252 = serde_json::from_str(&stark_proof.witness.to_string()).unwrap();
+
+    let proof = chain!(
+        config.into_iter(),
+        public_input.into_iter(),
+        unsent_commitment.into_iter(),
+        witness.into_iter()
+    );
+
+    Ok(proof.map(|f| f.to_string()).collect())
+}
+
 fn serialize(input: String) -> Result<String, Error> {
     let mut parsed: StarkProofExprs = parse(input)?.into();
 
@@ -174,32 +397,8 @@ fn serialize(input: String) -> Result<String, Error> {
     let unsent_commitment: VecFelt252 =
         serde_json::from_str(&parsed.unsent_commitment.to_string()).unwrap();
 
-    let fri_witness = match parsed.witness.0.pop().unwrap() {
-        Expr::Array(witness) => witness,
-        _ => panic!("Expected witness to be an array"),
-    };
-    let mut fri_layers = vec![];
-    let mut i = Felt252::from(0);
-    let mut reach_0_count = 2;
-    fri_witness.into_iter().for_each(|elem| {
-        let elem = match elem {
-            Expr::Value(s) => <Felt252 as Num>::from_str_radix(s.as_str(), 10).unwrap(),
-            _ => panic!("Expected value"),
-        };
-        if i == Felt252::from(0) {
-            if reach_0_count == 2 {
-                fri_layers.push(vec![]);
-                reach_0_count = 0;
-            }
-            reach_0_count += 1;
-
-            i = elem.clone();
-        } else {
-            i -= Felt252::from(1);
-        }
-        fri_layers.last_mut().unwrap().push(elem);
-    });
-
+    // the FRI witness is sent in the step calls
+    parsed.witness.0.pop();
     parsed.witness.0.push(Expr::Array(vec![]));
     let witness: VecFelt252 = serde_json::from_str(&parsed.witness.to_string()).unwrap();
 
@@ -218,6 +417,16 @@ fn serialize(input: String) -> Result<String, Error> {
     Ok(calldata_string)
 }
 
+fn read_fact_topologies(
+    fact_topologies_file: &Path,
+) -> Result<Vec<stark_evm_adapter::oods_statement::FactTopology>, Error> {
+    let fact_topologies: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(fact_topologies_file)?)?;
+    Ok(serde_json::from_value(
+        fact_topologies["fact_topologies"].clone(),
+    )?)
+}
+
 fn parse_bootloader_proof_file(
     proof_file: &Path,
     annotation_file: Option<PathBuf>,
@@ -0,0 +1,231 @@
+use super::Error;
+use crate::args::{CalldataFormat, SerializationType};
+use crate::compat::VerifierConfig;
+use crate::utils;
+use serde::Serialize;
+use starknet_crypto::Felt;
+use std::path::PathBuf;
+
+/// Name of the manifest file written next to split proofs
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+pub const VERIFY_PROOF_FULL: &str = "verify_proof_full_and_register_fact";
+pub const VERIFY_PROOF_INITIAL: &str = "verify_proof_initial";
+pub const VERIFY_PROOF_STEP: &str = "verify_proof_step";
+pub const VERIFY_PROOF_FINAL: &str = "verify_proof_final_and_register_fact";
+
+/// Calldata of a call to an Integrity verifier entrypoint
+pub struct StarknetCall {
+    pub path: PathBuf,
+    pub entrypoint: &'static str,
+    pub calldata: Vec<Felt>,
+}
+
+impl StarknetCall {
+    pub fn new(path: PathBuf, entrypoint: &'static str, calldata: Vec<Felt>) -> Self {
+        StarknetCall {
+            path,
+            entrypoint,
+            calldata,
+        }
+    }
+
+    /// Whether the entrypoint takes the job id as its first argument
+    pub fn takes_job_id(&self) -> bool {
+        self.entrypoint != VERIFY_PROOF_FULL
+    }
+}
+
+/// Lists the calls needed to verify a proof on Starknet, in submission order
+#[derive(Debug, Serialize)]
+pub struct Manifest {
+    pub serialization_type: SerializationType,
+    pub format: CalldataFormat,
+    /// Job id of split proofs, passed as the first argument of every call
+    pub job_id: Option<String>,
+    /// Verifier config included at the start of the proof
+    pub verifier_config: VerifierConfig,
+    pub calls: Vec<ManifestCall>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ManifestCall {
+    pub file: String,
+    pub entrypoint: &'static str,
+    /// Number of felts in the file
+    pub felt_count: usize,
+    /// Size of the file in bytes
+    pub byte_size: usize,
+}
+
+/// Formats the calldata of a call
+///
+/// # Arguments
+///
+/// * `calldata` - The calldata of the call, without the job id
+/// * `format` - The format of the output
+/// * `job_id` - The job id, which is prepended to the calldata in the `sncast` format
+///
+/// # Returns
+///
+/// The formatted calldata and the number of felts it contains
+pub fn format_calldata(
+    calldata: &[Felt],
+    format: &CalldataFormat,
+    job_id: Option<&Felt>,
+) -> Result<(String, usize), Error> {
+    let hex =
+        |felts: &[Felt]| -> Vec<String> { felts.iter().map(|felt| felt.to_hex_string()).collect() };
+    Ok(match format {
+        CalldataFormat::decimal => (
+            calldata
+                .iter()
+                .map(|felt| felt.to_string())
+                .collect::<Vec<_>>()
+                .join(" "),
+            calldata.len(),
+        ),
+        CalldataFormat::hex => (hex(calldata).join(" "), calldata.len()),
+        CalldataFormat::json => (serde_json::to_string(&hex(calldata))?, calldata.len()),
+        CalldataFormat::sncast => {
+            let felts: Vec<String> = job_id
+                .into_iter()
+                .chain(calldata)
+                .map(|felt| felt.to_hex_string())
+                .collect();
+            (felts.join(" "), felts.len())
+        }
+    })
+}
+
+/// Writes the calldata of every call and returns the manifest listing them
+///
+/// # Arguments
+///
+/// * `calls` - The calls, in submission order
+/// * `serialization_type` - The serialization type of the proof
+/// * `format` - The format of the calldata files
+/// * `job_id` - The job id of split proofs
+/// * `verifier_config` - The verifier config included in the calldata
+///
+/// # Returns
+///
+/// A `Result` containing the `Manifest`, or an `Error` if a file cannot be written
+pub fn write_calls(
+    calls: &[StarknetCall],
+    serialization_type: SerializationType,
+    format: CalldataFormat,
+    job_id: Option<Felt>,
+    verifier_config: VerifierConfig,
+) -> Result<Manifest, Error> {
+    let mut manifest_calls = vec![];
+    for call in calls {
+        let call_job_id = job_id.as_ref().filter(|_| call.takes_job_id());
+        let (content, felt_count) = format_calldata(&call.calldata, &format, call_job_id)?;
+        std::fs::write(&call.path, &content)?;
+        manifest_calls.push(ManifestCall {
+            file: call
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            entrypoint: call.entrypoint,
+            felt_count,
+            byte_size: content.len(),
+        });
+    }
+    Ok(Manifest {
+        serialization_type,
+        format,
+        job_id: job_id.map(|job_id| job_id.to_string()),
+        verifier_config,
+        calls: manifest_calls,
+    })
+}
+
+/// Reads calldata written in any of the formats, either a JSON array or whitespace separated felts
+pub fn read_calldata(content: &str) -> Result<Vec<Felt>, Error> {
+    let values: Vec<String> = if content.trim_start().starts_with('[') {
+        serde_json::from_str(content)?
+    } else {
+        content.split_whitespace().map(str::to_string).collect()
+    };
+    values.iter().map(|value| parse_felt(value)).collect()
+}
+
+/// Encodes the verifier config as the short strings expected by Integrity
+pub fn verifier_config_calldata(config: &VerifierConfig) -> Vec<Felt> {
+    [
+        &config.layout,
+        &config.hasher,
+        &config.stone_version,
+        &config.memory_verification,
+    ]
+    .iter()
+    .map(|value| Felt::from_bytes_be_slice(value.as_bytes()))
+    .collect()
+}
+
+/// Parses a felt written in decimal or, with a `0x` prefix, in hex
+pub fn parse_felt(value: &str) -> Result<Felt, Error> {
+    utils::parse_felt(value).ok_or_else(|| Error::InvalidFelt(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(CalldataFormat::decimal, None, "1 255", 2)]
+    #[case(CalldataFormat::hex, None, "0x1 0xff", 2)]
+    #[case(CalldataFormat::json, None, "[\"0x1\",\"0xff\"]", 2)]
+    #[case(CalldataFormat::sncast, None, "0x1 0xff", 2)]
+    #[case(CalldataFormat::sncast, Some(42), "0x2a 0x1 0xff", 3)]
+    fn test_format_calldata(
+        #[case] format: CalldataFormat,
+        #[case] job_id: Option<u64>,
+        #[case] expected: &str,
+        #[case] expected_felt_count: usize,
+    ) {
+        let calldata = vec![Felt::from(1u64), Felt::from(255u64)];
+        let job_id = job_id.map(Felt::from);
+        let (content, felt_count) = format_calldata(&calldata, &format, job_id.as_ref()).unwrap();
+        assert_eq!(content, expected);
+        assert_eq!(felt_count, expected_felt_count);
+    }
+
+    #[rstest]
+    #[case("1 255")]
+    #[case("0x1 0xff")]
+    #[case("[\"0x1\",\"0xff\"]")]
+    fn test_read_calldata(#[case] content: &str) {
+        assert_eq!(
+            read_calldata(content).unwrap(),
+            vec![Felt::from(1u64), Felt::from(255u64)]
+        );
+    }
+
+    #[rstest]
+    fn test_verifier_config_calldata() {
+        let config = VerifierConfig {
+            layout: "recursive".to_string(),
+            hasher: "keccak_160_lsb".to_string(),
+            stone_version: "stone5".to_string(),
+            memory_verification: "strict".to_string(),
+        };
+        let calldata: Vec<String> = verifier_config_calldata(&config)
+            .iter()
+            .map(|felt| felt.to_hex_string())
+            .collect();
+        assert_eq!(
+            calldata,
+            vec![
+                "0x726563757273697665",
+                "0x6b656363616b5f3136305f6c7362",
+                "0x73746f6e6535",
+                "0x737472696374"
+            ]
+        );
+    }
+}
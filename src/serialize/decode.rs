@@ -0,0 +1,715 @@
+use super::Error;
+use crate::args::LayoutName;
+use starknet_crypto::Felt;
+use swiftness::transform_stark::TransformTo;
+use swiftness_air::public_memory::PublicInput;
+use swiftness_air::trace::config::Config as TracesConfig;
+use swiftness_air::trace::{
+    Decommitment as TracesDecommitment, UnsentCommitment as TracesUnsentCommitment,
+    Witness as TracesWitness,
+};
+use swiftness_air::types::{AddrValue, ContinuousPageHeader, Page, SegmentInfo};
+use swiftness_commitment::table::config::Config as TableCommitmentConfig;
+use swiftness_commitment::table::types::{
+    Decommitment as TableDecommitment, Witness as TableCommitmentWitness,
+};
+use swiftness_commitment::vector::config::Config as VectorCommitmentConfig;
+use swiftness_commitment::vector::types::Witness as VectorCommitmentWitness;
+use swiftness_fri::config::Config as FriConfig;
+use swiftness_fri::types::{
+    LayerWitness, UnsentCommitment as FriUnsentCommitment, Witness as FriWitness,
+};
+use swiftness_pow::config::Config as PowConfig;
+use swiftness_pow::pow::UnsentCommitment as PowUnsentCommitment;
+use swiftness_proof_parser::parse;
+use swiftness_stark::config::StarkConfig;
+use swiftness_stark::types::{StarkProof, StarkUnsentCommitment, StarkWitness};
+
+/// Reads the proof that the calldata of a proof file must encode
+///
+/// # Arguments
+///
+/// * `input` - The content of the proof file
+/// * `with_fri_witness` - Whether the calldata includes the FRI witness. The initial call of
+///   split proofs leaves it out, as it is sent in the step calls instead
+///
+/// # Returns
+///
+/// A `Result` containing the expected `StarkProof`, or an `Error` if the proof cannot be parsed
+pub fn expected_proof(input: &str, with_fri_witness: bool) -> Result<StarkProof, Error> {
+    let mut proof: StarkProof = parse(input.to_string())?.transform_to();
+    if !with_fri_witness {
+        proof.witness.fri_witness.layers.clear();
+    }
+    Ok(proof)
+}
+
+/// Rebuilds a proof from the calldata of `verify_proof_full_and_register_fact` or
+/// `verify_proof_initial`
+///
+/// The calldata is read as the Integrity serialization of a `StarkProof`, where arrays are
+/// prefixed with their number of felts, and some of them also with their number of items. The
+/// lengths of the arrays are checked against the config read from the calldata, and the layout of
+/// the public input against `layout`.
+///
+/// # Arguments
+///
+/// * `felts` - The calldata of the proof, without the verifier config
+/// * `layout` - The layout of the proof
+/// * `with_fri_witness` - Whether the calldata includes the FRI witness
+///
+/// # Returns
+///
+/// A `Result` containing the `StarkProof`, or an `Error` if the calldata does not encode a proof
+/// of the layout
+pub fn decode_proof(
+    felts: &[Felt],
+    layout: &LayoutName,
+    with_fri_witness: bool,
+) -> Result<StarkProof, Error> {
+    let mut reader = Reader::new(felts);
+    let proof = StarkProof {
+        config: read_config(&mut reader)?,
+        public_input: read_public_input(&mut reader)?,
+        unsent_commitment: read_unsent_commitment(&mut reader)?,
+        witness: read_witness(&mut reader)?,
+    };
+    reader.finish()?;
+
+    // Stone writes the layout name as a short string
+    if proof.public_input.layout != Felt::from_bytes_be_slice(layout.clone().to_str().as_bytes()) {
+        return Err(Error::Decode(format!(
+            "the public input is not of the {} layout",
+            layout
+        )));
+    }
+    check_lengths(&proof, with_fri_witness)?;
+    Ok(proof)
+}
+
+/// Checks that a decoded proof is the expected one
+///
+/// # Returns
+///
+/// `Ok` if the proofs are equal, or an `Error` naming the first part that differs
+pub fn compare(decoded: &StarkProof, expected: &StarkProof) -> Result<(), Error> {
+    if decoded.config != expected.config {
+        return Err(Error::CheckFailed("config"));
+    }
+    if decoded.public_input != expected.public_input {
+        return Err(Error::CheckFailed("public input"));
+    }
+    if decoded.unsent_commitment != expected.unsent_commitment {
+        return Err(Error::CheckFailed("unsent commitment"));
+    }
+    if decoded.witness != expected.witness {
+        return Err(Error::CheckFailed("witness"));
+    }
+    Ok(())
+}
+
+fn read_table_config(reader: &mut Reader) -> Result<TableCommitmentConfig, Error> {
+    Ok(TableCommitmentConfig {
+        n_columns: reader.felt()?,
+        vector: VectorCommitmentConfig {
+            height: reader.felt()?,
+            n_verifier_friendly_commitment_layers: reader.felt()?,
+        },
+    })
+}
+
+fn read_config(reader: &mut Reader) -> Result<StarkConfig, Error> {
+    let traces = TracesConfig {
+        original: read_table_config(reader)?,
+        interaction: read_table_config(reader)?,
+    };
+    let composition = read_table_config(reader)?;
+    let fri = FriConfig {
+        log_input_size: reader.felt()?,
+        n_layers: reader.felt()?,
+        inner_layers: reader
+            .felts(3)?
+            .chunks_exact(3)
+            .map(|config| TableCommitmentConfig {
+                n_columns: config[0],
+                vector: VectorCommitmentConfig {
+                    height: config[1],
+                    n_verifier_friendly_commitment_layers: config[2],
+                },
+            })
+            .collect(),
+        fri_step_sizes: reader.felts(1)?,
+        log_last_layer_degree_bound: reader.felt()?,
+    };
+    Ok(StarkConfig {
+        traces,
+        composition,
+        fri,
+        proof_of_work: PowConfig {
+            n_bits: to_int(reader.felt()?)?,
+        },
+        log_trace_domain_size: reader.felt()?,
+        n_queries: reader.felt()?,
+        log_n_cosets: reader.felt()?,
+        n_verifier_friendly_commitment_layers: reader.felt()?,
+    })
+}
+
+fn read_public_input(reader: &mut Reader) -> Result<PublicInput, Error> {
+    let log_n_steps = reader.felt()?;
+    let range_check_min = reader.felt()?;
+    let range_check_max = reader.felt()?;
+    let layout = reader.felt()?;
+    // only the dynamic layout has parameters, and it cannot be serialized for Starknet
+    if !reader.felts(1)?.is_empty() {
+        return Err(Error::Decode(
+            "the public input has dynamic layout parameters".to_string(),
+        ));
+    }
+    let segments = reader
+        .counted_felts(2)?
+        .chunks_exact(2)
+        .map(|segment| SegmentInfo {
+            begin_addr: segment[0],
+            stop_ptr: segment[1],
+        })
+        .collect();
+    let padding_addr = reader.felt()?;
+    let padding_value = reader.felt()?;
+    let main_page = reader
+        .counted_felts(2)?
+        .chunks_exact(2)
+        .map(|cell| AddrValue {
+            address: cell[0],
+            value: cell[1],
+        })
+        .collect();
+    let continuous_page_headers = reader
+        .counted_felts(4)?
+        .chunks_exact(4)
+        .map(|header| ContinuousPageHeader {
+            start_address: header[0],
+            size: header[1],
+            hash: header[2],
+            prod: header[3],
+        })
+        .collect();
+    Ok(PublicInput {
+        log_n_steps,
+        range_check_min,
+        range_check_max,
+        layout,
+        dynamic_params: None,
+        segments,
+        padding_addr,
+        padding_value,
+        main_page: Page(main_page),
+        continuous_page_headers,
+    })
+}
+
+fn read_unsent_commitment(reader: &mut Reader) -> Result<StarkUnsentCommitment, Error> {
+    Ok(StarkUnsentCommitment {
+        traces: TracesUnsentCommitment {
+            original: reader.felt()?,
+            interaction: reader.felt()?,
+        },
+        composition: reader.felt()?,
+        oods_values: reader.felts(1)?,
+        fri: FriUnsentCommitment {
+            inner_layers: reader.felts(1)?,
+            last_layer_coefficients: reader.felts(1)?,
+        },
+        proof_of_work: PowUnsentCommitment {
+            nonce: to_int(reader.felt()?)?,
+        },
+    })
+}
+
+fn read_witness(reader: &mut Reader) -> Result<StarkWitness, Error> {
+    let decommitment = |reader: &mut Reader| -> Result<TableDecommitment, Error> {
+        Ok(TableDecommitment {
+            values: reader.counted_felts(1)?,
+        })
+    };
+    let witness = |reader: &mut Reader| -> Result<TableCommitmentWitness, Error> {
+        Ok(TableCommitmentWitness {
+            vector: VectorCommitmentWitness {
+                authentications: reader.counted_felts(1)?,
+            },
+        })
+    };
+    let traces_decommitment = TracesDecommitment {
+        original: decommitment(reader)?,
+        interaction: decommitment(reader)?,
+    };
+    let traces_witness = TracesWitness {
+        original: witness(reader)?,
+        interaction: witness(reader)?,
+    };
+    let composition_decommitment = decommitment(reader)?;
+    let composition_witness = witness(reader)?;
+
+    // the layers are prefixed with their total number of felts
+    let fri_felts = reader.felts(1)?;
+    let mut layers_reader = Reader::new(&fri_felts);
+    let mut layers = vec![];
+    while layers_reader.position < fri_felts.len() {
+        layers.push(LayerWitness {
+            leaves: layers_reader.array(1)?,
+            table_witness: TableCommitmentWitness {
+                vector: VectorCommitmentWitness {
+                    authentications: layers_reader.array(1)?,
+                },
+            },
+        });
+    }
+
+    Ok(StarkWitness {
+        traces_decommitment,
+        traces_witness,
+        composition_decommitment,
+        composition_witness,
+        fri_witness: FriWitness { layers },
+    })
+}
+
+/// Checks the lengths of the arrays of a decoded proof against its config
+fn check_lengths(proof: &StarkProof, with_fri_witness: bool) -> Result<(), Error> {
+    let config = &proof.config;
+    let n_queries: usize = to_int(config.n_queries)?;
+    let n_layers: usize = to_int(config.fri.n_layers)?;
+    let n_inner_layers = n_layers
+        .checked_sub(1)
+        .ok_or_else(|| Error::Decode("the config has no FRI layer".to_string()))?;
+    let n_last_layer_coefficients = 1usize
+        .checked_shl(to_int(config.fri.log_last_layer_degree_bound)?)
+        .ok_or_else(|| Error::Decode("invalid last FRI layer degree bound".to_string()))?;
+    let n_values = |table: &TableCommitmentConfig| -> Result<usize, Error> {
+        n_queries
+            .checked_mul(to_int(table.n_columns)?)
+            .ok_or_else(|| Error::Decode("invalid number of columns".to_string()))
+    };
+    let witness = &proof.witness;
+    let fri_commitment = &proof.unsent_commitment.fri;
+
+    let lengths = [
+        (
+            "inner FRI layer configs",
+            config.fri.inner_layers.len(),
+            n_inner_layers,
+        ),
+        ("FRI step sizes", config.fri.fri_step_sizes.len(), n_layers),
+        (
+            "inner FRI layer commitments",
+            fri_commitment.inner_layers.len(),
+            n_inner_layers,
+        ),
+        (
+            "last FRI layer coefficients",
+            fri_commitment.last_layer_coefficients.len(),
+            n_last_layer_coefficients,
+        ),
+        (
+            "original trace values",
+            witness.traces_decommitment.original.values.len(),
+            n_values(&config.traces.original)?,
+        ),
+        (
+            "interaction trace values",
+            witness.traces_decommitment.interaction.values.len(),
+            n_values(&config.traces.interaction)?,
+        ),
+        (
+            "composition values",
+            witness.composition_decommitment.values.len(),
+            n_values(&config.composition)?,
+        ),
+        (
+            "FRI witness layers",
+            witness.fri_witness.layers.len(),
+            if with_fri_witness { n_inner_layers } else { 0 },
+        ),
+    ];
+    for (name, len, expected) in lengths {
+        if len != expected {
+            return Err(Error::Decode(format!(
+                "the calldata has {} {} but the config implies {}",
+                len, name, expected
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn to_int<T: TryFrom<num_bigint::BigUint>>(felt: Felt) -> Result<T, Error> {
+    T::try_from(felt.to_biguint()).map_err(|_| Error::Decode(format!("invalid integer {}", felt)))
+}
+
+/// A FRI layer sent in a `verify_proof_step` call, as the `FriLayerWitness` of Integrity
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FriLayerWitness {
+    pub leaves: Vec<Felt>,
+    pub authentications: Vec<Felt>,
+}
+
+/// The data sent after the FRI states of a split proof call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FriCallData {
+    /// The witness of the FRI layer verified by a `verify_proof_step` call
+    Layer(FriLayerWitness),
+    /// The coefficients of the last FRI layer, sent in `verify_proof_final_and_register_fact`
+    LastLayerCoefficients(Vec<Felt>),
+}
+
+/// A `verify_proof_step` or `verify_proof_final_and_register_fact` call rebuilt from its calldata
+///
+/// The calldata is the Cairo serialization of the `FriVerificationStateConstant` and
+/// `FriVerificationStateVariable` of Integrity, followed by the `FriCallData` of the call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedFriCall {
+    /// The felts of the constant state, which is the same in every call
+    pub const_state: Vec<Felt>,
+    /// Number of inner FRI layers
+    pub n_layers: Felt,
+    /// Commitment hashes of the inner FRI layers
+    pub layer_commitments: Vec<Felt>,
+    /// Step sizes of the inner FRI layers
+    pub step_sizes: Vec<Felt>,
+    /// Index of the FRI layer verified by the call
+    pub iter: Felt,
+    pub data: FriCallData,
+}
+
+/// Rebuilds a step or final call of a split proof from its calldata
+///
+/// # Arguments
+///
+/// * `felts` - The calldata of the call, without the job id
+/// * `is_final` - Whether the call is `verify_proof_final_and_register_fact`
+///
+/// # Returns
+///
+/// A `Result` containing the `DecodedFriCall`, or an `Error` if the calldata is too short or
+/// too long
+pub fn decode_fri_call(felts: &[Felt], is_final: bool) -> Result<DecodedFriCall, Error> {
+    let mut reader = Reader::new(felts);
+    let n_layers = reader.felt()?;
+    // each table commitment is its config (n_columns, height, n_verifier_friendly_layers), the
+    // config of its vector commitment (height, n_verifier_friendly_layers) and its hash
+    let layer_commitments = reader
+        .array(6)?
+        .chunks(6)
+        .map(|commitment| commitment[5])
+        .collect();
+    reader.array(1)?;
+    let step_sizes = reader.array(1)?;
+    reader.felt()?;
+    let const_state = felts[..reader.position].to_vec();
+
+    let iter = reader.felt()?;
+    // each query is its index, y value and x inverse value
+    reader.array(3)?;
+    let data = if is_final {
+        FriCallData::LastLayerCoefficients(reader.array(1)?)
+    } else {
+        FriCallData::Layer(FriLayerWitness {
+            leaves: reader.array(1)?,
+            authentications: reader.array(1)?,
+        })
+    };
+    reader.finish()?;
+
+    Ok(DecodedFriCall {
+        const_state,
+        n_layers,
+        layer_commitments,
+        step_sizes,
+        iter,
+        data,
+    })
+}
+
+/// Checks that the step and final calls of a split proof encode the FRI layers of the proof
+///
+/// The witness of each FRI layer and the coefficients of the last layer are compared with the
+/// proof, as well as the commitments and step sizes of the layers recorded in the constant state.
+///
+/// # Arguments
+///
+/// * `input` - The content of the proof file
+/// * `calls` - The calldata of the step calls followed by the final call, without the job id
+///
+/// # Returns
+///
+/// `Ok` if the calls encode the proof, or an `Error` naming the first part that differs
+pub fn check_fri_calls(input: &str, calls: &[Vec<Felt>]) -> Result<(), Error> {
+    let proof: StarkProof = parse(input.to_string())?.transform_to();
+    let (final_, steps) = calls
+        .split_last()
+        .ok_or(Error::CheckFailed("last FRI layer"))?;
+    let final_ = decode_fri_call(final_, true)?;
+    let steps = steps
+        .iter()
+        .map(|felts| decode_fri_call(felts, false))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (i, call) in steps.iter().chain([&final_]).enumerate() {
+        if call.const_state != final_.const_state {
+            return Err(Error::CheckFailed("constant FRI state"));
+        }
+        if call.iter != Felt::from(i as u64) {
+            return Err(Error::CheckFailed("FRI layer order"));
+        }
+    }
+
+    let fri_commitment = &proof.unsent_commitment.fri;
+    if final_.n_layers != Felt::from(steps.len() as u64)
+        || final_.layer_commitments != fri_commitment.inner_layers
+    {
+        return Err(Error::CheckFailed("FRI layer commitments"));
+    }
+    if Some(final_.step_sizes.as_slice()) != proof.config.fri.fri_step_sizes.get(1..) {
+        return Err(Error::CheckFailed("FRI step sizes"));
+    }
+
+    let witness: Vec<FriCallData> = steps.into_iter().map(|call| call.data).collect();
+    let expected: Vec<FriCallData> = proof
+        .witness
+        .fri_witness
+        .layers
+        .iter()
+        .map(|layer| {
+            FriCallData::Layer(FriLayerWitness {
+                leaves: layer.leaves.clone(),
+                authentications: layer.table_witness.vector.authentications.clone(),
+            })
+        })
+        .collect();
+    if witness != expected {
+        return Err(Error::CheckFailed("FRI witness"));
+    }
+    if final_.data
+        != FriCallData::LastLayerCoefficients(fri_commitment.last_layer_coefficients.clone())
+    {
+        return Err(Error::CheckFailed("last FRI layer"));
+    }
+    Ok(())
+}
+
+/// Reads the felts of a call in order, with arrays prefixed by their length
+struct Reader<'a> {
+    felts: &'a [Felt],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(felts: &'a [Felt]) -> Self {
+        Reader { felts, position: 0 }
+    }
+
+    fn felt(&mut self) -> Result<Felt, Error> {
+        let felt = self
+            .felts
+            .get(self.position)
+            .ok_or_else(|| Error::Decode("the calldata ends before the call".to_string()))?;
+        self.position += 1;
+        Ok(*felt)
+    }
+
+    /// Reads an array of items of `item_size` felts and returns the felts of its items
+    fn array(&mut self, item_size: usize) -> Result<Vec<Felt>, Error> {
+        let len = self.felt()?;
+        let n_felts = to_int::<usize>(len)
+            .ok()
+            .and_then(|len| len.checked_mul(item_size))
+            .ok_or_else(|| Error::Decode(format!("invalid array length {}", len)))?;
+        self.take(n_felts)
+    }
+
+    /// Reads an array prefixed with its number of felts, made of items of `item_size` felts
+    fn felts(&mut self, item_size: usize) -> Result<Vec<Felt>, Error> {
+        let len = self.felt()?;
+        let n_felts = to_int::<usize>(len)
+            .ok()
+            .filter(|n_felts| n_felts % item_size == 0)
+            .ok_or_else(|| Error::Decode(format!("invalid array length {}", len)))?;
+        self.take(n_felts)
+    }
+
+    /// Reads the number of items of an array, followed by the array prefixed with its number of
+    /// felts
+    fn counted_felts(&mut self, item_size: usize) -> Result<Vec<Felt>, Error> {
+        let n_items = self.felt()?;
+        let felts = self.felts(item_size)?;
+        if Felt::from(felts.len() / item_size) != n_items {
+            return Err(Error::Decode(format!(
+                "an array of {} items has {} felts",
+                n_items,
+                felts.len()
+            )));
+        }
+        Ok(felts)
+    }
+
+    fn take(&mut self, n_felts: usize) -> Result<Vec<Felt>, Error> {
+        let end = self
+            .position
+            .checked_add(n_felts)
+            .filter(|end| *end <= self.felts.len())
+            .ok_or_else(|| Error::Decode("the calldata ends before the call".to_string()))?;
+        let items = self.felts[self.position..end].to_vec();
+        self.position = end;
+        Ok(items)
+    }
+
+    fn finish(self) -> Result<(), Error> {
+        let remaining = self.felts.len() - self.position;
+        if remaining > 0 {
+            return Err(Error::Decode(format!(
+                "{} felts are left after the call",
+                remaining
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::path::Path;
+
+    const MONOLITH_PROOF: &str = "tests/resources/proofs/starknet/monolith";
+    const SPLIT_PROOF: &str = "tests/resources/proofs/starknet/split/layouts/starknet";
+
+    fn read_resource(path: &str) -> String {
+        std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join(path)).unwrap()
+    }
+
+    // the calldata of a proof, without the verifier config
+    fn proof_calldata(path: &str) -> Vec<Felt> {
+        super::super::calldata::read_calldata(&read_resource(path)).unwrap()[4..].to_vec()
+    }
+
+    fn felts(values: &[u64]) -> Vec<Felt> {
+        values.iter().map(|value| Felt::from(*value)).collect()
+    }
+
+    #[rstest]
+    #[case(
+        "tests/resources/proofs/starknet/monolith/serialized",
+        "tests/resources/proofs/starknet/monolith/cairo0_stone5_keccak_160_lsb_example_proof.json",
+        LayoutName::recursive,
+        true
+    )]
+    #[case(
+        "tests/resources/proofs/starknet/split/layouts/starknet/serialized/initial",
+        "tests/resources/proofs/starknet/split/layouts/starknet/cairo0_example_proof.json",
+        LayoutName::starknet,
+        false
+    )]
+    fn test_decode_proof(
+        #[case] calldata: &str,
+        #[case] proof: &str,
+        #[case] layout: LayoutName,
+        #[case] with_fri_witness: bool,
+    ) {
+        let decoded = decode_proof(&proof_calldata(calldata), &layout, with_fri_witness).unwrap();
+        let expected = expected_proof(&read_resource(proof), with_fri_witness).unwrap();
+        assert_eq!(
+            decoded.witness.fri_witness.layers.len(),
+            expected.witness.fri_witness.layers.len()
+        );
+        assert!(compare(&decoded, &expected).is_ok());
+    }
+
+    #[rstest]
+    fn test_compare_names_the_part_that_differs() {
+        let calldata = proof_calldata(&format!("{}/serialized", MONOLITH_PROOF));
+        let mut decoded = decode_proof(&calldata, &LayoutName::recursive, true).unwrap();
+        let expected = decode_proof(&calldata, &LayoutName::recursive, true).unwrap();
+        let values = &mut decoded.witness.composition_decommitment.values;
+        values[0] = values[0] + Felt::ONE;
+        match compare(&decoded, &expected) {
+            Err(Error::CheckFailed(part)) => assert_eq!(part, "witness"),
+            _ => panic!("Expected the witness to differ"),
+        }
+    }
+
+    #[rstest]
+    // the layout of the public input is recursive
+    #[case(LayoutName::starknet, true)]
+    // the FRI witness is only left out of the initial call of split proofs
+    #[case(LayoutName::recursive, false)]
+    fn test_decode_proof_with_wrong_settings(
+        #[case] layout: LayoutName,
+        #[case] with_fri_witness: bool,
+    ) {
+        let calldata = proof_calldata(&format!("{}/serialized", MONOLITH_PROOF));
+        assert!(matches!(
+            decode_proof(&calldata, &layout, with_fri_witness),
+            Err(Error::Decode(_))
+        ));
+    }
+
+    #[rstest]
+    #[case(-1)]
+    #[case(1)]
+    fn test_decode_proof_with_wrong_length(#[case] extra_felts: isize) {
+        let mut calldata = proof_calldata(&format!("{}/serialized/initial", SPLIT_PROOF));
+        calldata.resize(
+            calldata.len().checked_add_signed(extra_felts).unwrap(),
+            Felt::ZERO,
+        );
+        assert!(matches!(
+            decode_proof(&calldata, &LayoutName::starknet, false),
+            Err(Error::Decode(_))
+        ));
+    }
+
+    // two inner layers, one query, and the data of the call
+    fn fri_call(iter: u64, data: &[u64]) -> Vec<Felt> {
+        let mut values = vec![2, 2];
+        values.extend([16, 10, 0, 10, 0, 100, 16, 8, 0, 8, 0, 101]);
+        values.extend([2, 3, 4, 2, 4, 2, 55, iter, 1, 7, 8, 9]);
+        values.extend(data);
+        felts(&values)
+    }
+
+    #[rstest]
+    fn test_decode_fri_call() {
+        let step = decode_fri_call(&fri_call(0, &[2, 11, 12, 1, 13]), false).unwrap();
+        assert_eq!(step.n_layers, Felt::from(2u64));
+        assert_eq!(step.layer_commitments, felts(&[100, 101]));
+        assert_eq!(step.step_sizes, felts(&[4, 2]));
+        assert_eq!(step.const_state.len(), 21);
+        assert_eq!(step.iter, Felt::ZERO);
+        assert_eq!(
+            step.data,
+            FriCallData::Layer(FriLayerWitness {
+                leaves: felts(&[11, 12]),
+                authentications: felts(&[13]),
+            })
+        );
+
+        let final_ = decode_fri_call(&fri_call(2, &[2, 21, 22]), true).unwrap();
+        assert_eq!(final_.const_state, step.const_state);
+        assert_eq!(final_.iter, Felt::from(2u64));
+        assert_eq!(
+            final_.data,
+            FriCallData::LastLayerCoefficients(felts(&[21, 22]))
+        );
+    }
+
+    #[rstest]
+    #[case(&[2, 11, 12, 1])]
+    #[case(&[2, 11, 12, 1, 13, 14])]
+    fn test_decode_fri_call_with_wrong_length(#[case] data: &[u64]) {
+        assert!(matches!(
+            decode_fri_call(&fri_call(0, data), false),
+            Err(Error::Decode(_))
+        ));
+    }
+}
@@ -0,0 +1,202 @@
+use super::Error;
+use ethers::abi::Token;
+use ethers::types::{Bytes, U256};
+use stark_evm_adapter::annotated_proof::AnnotatedProof;
+use stark_evm_adapter::annotation_parser::split_fri_merkle_statements;
+use stark_evm_adapter::oods_statement::FactTopology;
+
+const VERIFY_MERKLE: &str = "verifyMerkle(uint256[],uint256[],uint256,uint256)";
+const VERIFY_FRI: &str = "verifyFRI(uint256[],uint256[],uint256,uint256,uint256)";
+const REGISTER_CONTINUOUS_MEMORY_PAGE: &str =
+    "registerContinuousMemoryPage(uint256,uint256[],uint256,uint256,uint256)";
+const VERIFY_PROOF_AND_REGISTER: &str =
+    "verifyProofAndRegister(uint256[],uint256[],uint256[],uint256[],uint256)";
+
+// id of the Cairo verifier of the starknet layout in the GPS statement verifier
+const CAIRO_VERIFIER_ID: u64 = 6;
+
+// gas charged for every transaction and for each byte of its calldata
+const TRANSACTION_GAS: u64 = 21_000;
+const ZERO_BYTE_GAS: u64 = 4;
+const NON_ZERO_BYTE_GAS: u64 = 16;
+
+/// A transaction needed to verify a proof on Ethereum
+pub struct EvmTransaction {
+    /// Name of the verifier contract the transaction is sent to
+    pub contract: &'static str,
+    pub calldata: Bytes,
+}
+
+/// Builds the calldata of the transactions verifying a bootloader proof with the GPS verifier
+///
+/// The transactions are returned in submission order: the trace Merkle statements, the FRI
+/// Merkle statements, the continuous memory pages and finally the main proof, which can only be
+/// verified once the facts of the previous transactions are registered. The calls are
+/// ABI-encoded from the split proof, without any provider or signer.
+///
+/// # Arguments
+///
+/// * `annotated_proof_json` - The proof with its annotations and extra annotations
+/// * `fact_topologies` - The fact topologies of the tasks run by the bootloader
+///
+/// # Returns
+///
+/// A `Result` containing the transactions, or an `Error` if the proof cannot be split
+pub fn split_proof_calldata(
+    annotated_proof_json: &str,
+    fact_topologies: Vec<FactTopology>,
+) -> Result<Vec<EvmTransaction>, Error> {
+    let annotated_proof: AnnotatedProof = serde_json::from_str(annotated_proof_json)?;
+    let split_proofs = split_fri_merkle_statements(annotated_proof)
+        .map_err(|e| Error::EvmAdapter(e.to_string()))?;
+
+    let mut transactions = vec![];
+    for i in 0..split_proofs.merkle_statements.len() {
+        let key = format!("Trace {}", i);
+        let statement = split_proofs
+            .merkle_statements
+            .get(&key)
+            .ok_or_else(|| Error::EvmAdapter(format!("missing merkle statement {}", key)))?;
+        // the Merkle queue is a list of (index, value) pairs
+        let merkle_queue = statement
+            .merkle_queue_indices
+            .iter()
+            .zip(&statement.merkle_queue_values)
+            .flat_map(|(index, value)| [*index, *value])
+            .collect();
+        transactions.push(EvmTransaction {
+            contract: "merkle_statement",
+            calldata: encode_call(
+                VERIFY_MERKLE,
+                vec![
+                    uint_array(statement.proof.clone()),
+                    uint_array(merkle_queue),
+                    Token::Uint(U256::from(statement.merkle_height)),
+                    Token::Uint(statement.expected_root),
+                ],
+            ),
+        });
+    }
+    for statement in &split_proofs.fri_merkle_statements {
+        // the FRI queue is a list of (index, value, inverse) triplets ended by a zero
+        let fri_queue = [statement.input_interleaved.clone(), vec![U256::zero()]].concat();
+        transactions.push(EvmTransaction {
+            contract: "fri_statement",
+            calldata: encode_call(
+                VERIFY_FRI,
+                vec![
+                    uint_array(statement.proof.clone()),
+                    uint_array(fri_queue),
+                    Token::Uint(statement.evaluation_point),
+                    Token::Uint(U256::from(statement.fri_step_size)),
+                    Token::Uint(statement.expected_root),
+                ],
+            ),
+        });
+    }
+
+    let main_proof = &split_proofs.main_proof;
+    let (_, continuous_pages) = main_proof.memory_page_registration_args();
+    for page in continuous_pages {
+        transactions.push(EvmTransaction {
+            contract: "memory_page_fact_registry",
+            calldata: encode_call(
+                REGISTER_CONTINUOUS_MEMORY_PAGE,
+                vec![
+                    Token::Uint(page.start_address),
+                    uint_array(page.values),
+                    Token::Uint(page.z),
+                    Token::Uint(page.alpha),
+                    Token::Uint(page.prime),
+                ],
+            ),
+        });
+    }
+
+    let task_metadata: Vec<U256> = main_proof.generate_tasks_metadata(true, fact_topologies);
+    // the interaction elements follow the public input in the Cairo auxiliary input
+    let cairo_aux_input = [
+        main_proof.public_input.clone(),
+        vec![main_proof.interaction_z, main_proof.interaction_alpha],
+    ]
+    .concat();
+    transactions.push(EvmTransaction {
+        contract: "gps_statement_verifier",
+        calldata: encode_call(
+            VERIFY_PROOF_AND_REGISTER,
+            vec![
+                uint_array(main_proof.proof_parameters.clone()),
+                uint_array(main_proof.proof.clone()),
+                uint_array(task_metadata),
+                uint_array(cairo_aux_input),
+                Token::Uint(U256::from(CAIRO_VERIFIER_ID)),
+            ],
+        ),
+    });
+
+    Ok(transactions)
+}
+
+/// ABI-encodes a call to the function with the given signature
+fn encode_call(signature: &str, arguments: Vec<Token>) -> Bytes {
+    let mut data = ethers::utils::id(signature).to_vec();
+    data.extend(ethers::abi::encode(&arguments));
+    Bytes::from(data)
+}
+
+fn uint_array(values: Vec<U256>) -> Token {
+    Token::Array(values.into_iter().map(Token::Uint).collect())
+}
+
+/// Gas paid for a transaction and its calldata before execution, a lower bound of its gas
+pub fn intrinsic_gas(calldata: &[u8]) -> u64 {
+    TRANSACTION_GAS
+        + calldata
+            .iter()
+            .map(|byte| {
+                if *byte == 0 {
+                    ZERO_BYTE_GAS
+                } else {
+                    NON_ZERO_BYTE_GAS
+                }
+            })
+            .sum::<u64>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn test_encode_call() {
+        let calldata = encode_call(
+            REGISTER_CONTINUOUS_MEMORY_PAGE,
+            vec![
+                Token::Uint(U256::from(1)),
+                uint_array(vec![U256::from(2)]),
+                Token::Uint(U256::from(3)),
+                Token::Uint(U256::from(4)),
+                Token::Uint(U256::from(5)),
+            ],
+        );
+        // selector, four static words, the array offset, its length and its value
+        assert_eq!(calldata.len(), 4 + 7 * 32);
+        assert_eq!(
+            calldata[..4],
+            ethers::utils::id(REGISTER_CONTINUOUS_MEMORY_PAGE)
+        );
+        // the array is encoded after the static words, at an offset of five words
+        assert_eq!(
+            U256::from_big_endian(&calldata[4 + 32..4 + 64]),
+            U256::from(160)
+        );
+    }
+
+    #[rstest]
+    #[case(&[], 21_000)]
+    #[case(&[0, 1, 0, 2], 21_040)]
+    fn test_intrinsic_gas(#[case] calldata: &[u8], #[case] expected: u64) {
+        assert_eq!(intrinsic_gas(calldata), expected);
+    }
+}
@@ -0,0 +1,435 @@
+use super::calldata::{Manifest, StarknetCall};
+use super::evm::{intrinsic_gas, EvmTransaction};
+use super::Error;
+use crate::args::Network;
+use crate::config::{FriParameters, ProofParameters, StarkParameters};
+use crate::fri::{
+    compute_fri_step_list, FriComputer, ProofSizeFriComputer, DEFAULT_MAX_FRI_STEP,
+    LOG_CPU_ROWS_PER_STEP,
+};
+use crate::prover::PublicInput;
+use serde::Deserialize;
+use std::fmt;
+
+// Starknet limit on the number of calldata felts of a transaction
+const STARKNET_MAX_CALLDATA_LENGTH: usize = 4000;
+// felts added by the account to the arguments of a single call: the number of calls, the
+// contract address, the entrypoint selector and the length of the arguments
+const ACCOUNT_CALL_OVERHEAD: usize = 4;
+// gas charged by Starknet for each calldata felt, as L1 gas in the fee model before v0.13.4 and
+// as L2 gas since then
+const L1_GAS_PER_CALLDATA_FELT: f64 = 0.128;
+const L2_GAS_PER_CALLDATA_FELT: u64 = 5120;
+// size of the largest transaction accepted by the mempool of Ethereum clients
+const ETHEREUM_MAX_TRANSACTION_SIZE: usize = 128 * 1024;
+// proof of work bits beyond which grinding noticeably slows the prover down
+const MAX_SUGGESTED_PROOF_OF_WORK_BITS: u32 = 32;
+
+/// The parts of a Stone proof file that determine its size
+#[derive(Debug, Deserialize)]
+struct ProofFile {
+    #[serde(default)]
+    proof_parameters: ProofParameters,
+    public_input: PublicInput,
+}
+
+/// Size and estimated cost of the calldata of a transaction
+#[derive(Debug)]
+pub struct TransactionReport {
+    pub file: String,
+    /// Entrypoint of a Starknet call, or verifier contract of an Ethereum transaction
+    pub target: &'static str,
+    /// Number of felts passed to the entrypoint of a Starknet call
+    pub felt_count: Option<usize>,
+    pub byte_size: usize,
+    /// Whether the transaction exceeds the calldata limit of the network
+    pub exceeds_limit: bool,
+    /// L1 gas of the calldata of a Starknet call, or intrinsic gas of an Ethereum transaction,
+    /// which leaves out the gas spent executing the verifier
+    pub estimated_l1_gas: u64,
+    /// L2 gas of the calldata of a Starknet call
+    pub estimated_l2_gas: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct CalldataReport {
+    pub network: Network,
+    pub transactions: Vec<TransactionReport>,
+    /// Changes of the prover parameters that would shrink the proof
+    pub suggestions: Vec<String>,
+}
+
+impl CalldataReport {
+    fn gas_label(&self) -> &'static str {
+        match self.network {
+            Network::starknet => "estimated L1 gas",
+            Network::ethereum => "intrinsic gas",
+        }
+    }
+}
+
+impl fmt::Display for CalldataReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for transaction in &self.transactions {
+            write!(f, "{} ({}): ", transaction.file, transaction.target)?;
+            if let Some(felt_count) = transaction.felt_count {
+                write!(f, "{} felts, ", felt_count)?;
+            }
+            write!(
+                f,
+                "{} bytes, {} {}",
+                transaction.byte_size,
+                self.gas_label(),
+                transaction.estimated_l1_gas
+            )?;
+            if let Some(l2_gas) = transaction.estimated_l2_gas {
+                write!(f, ", estimated L2 gas {}", l2_gas)?;
+            }
+            writeln!(f)?;
+            if transaction.exceeds_limit {
+                match self.network {
+                    Network::starknet => writeln!(
+                        f,
+                        "  exceeds the Starknet limit of {} calldata felts",
+                        STARKNET_MAX_CALLDATA_LENGTH
+                    )?,
+                    Network::ethereum => writeln!(
+                        f,
+                        "  exceeds the Ethereum limit of {} bytes per transaction",
+                        ETHEREUM_MAX_TRANSACTION_SIZE
+                    )?,
+                }
+            }
+        }
+        write!(
+            f,
+            "Total {}: {}",
+            self.gas_label(),
+            self.transactions
+                .iter()
+                .map(|transaction| transaction.estimated_l1_gas)
+                .sum::<u64>()
+        )?;
+        if self.network == Network::starknet {
+            write!(
+                f,
+                ", L2 gas: {}",
+                self.transactions
+                    .iter()
+                    .filter_map(|transaction| transaction.estimated_l2_gas)
+                    .sum::<u64>()
+            )?;
+        }
+        writeln!(f)?;
+        for suggestion in &self.suggestions {
+            writeln!(f, "Suggestion: {}", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reports the size and cost of the calls verifying a proof on Starknet
+///
+/// The costs only cover the calldata of the calls, not the execution of the verifier.
+///
+/// # Arguments
+///
+/// * `calls` - The calls, in submission order
+/// * `manifest` - The manifest returned when writing the calls
+/// * `has_job_id` - Whether the calls of split proofs take a job id
+/// * `input` - The content of the proof file
+///
+/// # Returns
+///
+/// A `Result` containing the `CalldataReport`, or an `Error` if the proof parameters cannot be read
+pub fn starknet_report(
+    calls: &[StarknetCall],
+    manifest: &Manifest,
+    has_job_id: bool,
+    input: &str,
+) -> Result<CalldataReport, Error> {
+    let transactions = calls
+        .iter()
+        .zip(&manifest.calls)
+        .map(|(call, manifest_call)| {
+            let felt_count = call.calldata.len() + usize::from(has_job_id && call.takes_job_id());
+            let transaction_felts = felt_count + ACCOUNT_CALL_OVERHEAD;
+            TransactionReport {
+                file: manifest_call.file.clone(),
+                target: call.entrypoint,
+                felt_count: Some(felt_count),
+                byte_size: manifest_call.byte_size,
+                exceeds_limit: transaction_felts > STARKNET_MAX_CALLDATA_LENGTH,
+                estimated_l1_gas: (transaction_felts as f64 * L1_GAS_PER_CALLDATA_FELT).ceil()
+                    as u64,
+                estimated_l2_gas: Some(transaction_felts as u64 * L2_GAS_PER_CALLDATA_FELT),
+            }
+        })
+        .collect();
+    Ok(CalldataReport {
+        network: Network::starknet,
+        transactions,
+        suggestions: suggest_parameters(input)?,
+    })
+}
+
+/// Reports the size and intrinsic gas of the transactions verifying a proof on Ethereum
+///
+/// # Arguments
+///
+/// * `transactions` - The transactions, in submission order
+/// * `files` - The names of the calldata files of the transactions
+/// * `input` - The content of the proof file
+///
+/// # Returns
+///
+/// A `Result` containing the `CalldataReport`, or an `Error` if the proof parameters cannot be read
+pub fn ethereum_report(
+    transactions: &[EvmTransaction],
+    files: &[String],
+    input: &str,
+) -> Result<CalldataReport, Error> {
+    let transactions = transactions
+        .iter()
+        .zip(files)
+        .map(|(transaction, file)| TransactionReport {
+            file: file.clone(),
+            target: transaction.contract,
+            felt_count: None,
+            byte_size: transaction.calldata.len(),
+            exceeds_limit: transaction.calldata.len() > ETHEREUM_MAX_TRANSACTION_SIZE,
+            estimated_l1_gas: intrinsic_gas(&transaction.calldata),
+            estimated_l2_gas: None,
+        })
+        .collect();
+    Ok(CalldataReport {
+        network: Network::ethereum,
+        transactions,
+        suggestions: suggest_parameters(input)?,
+    })
+}
+
+fn suggest_parameters(input: &str) -> Result<Vec<String>, Error> {
+    let proof: ProofFile = serde_json::from_str(input)?;
+    Ok(match &proof.proof_parameters.stark {
+        Some(stark) => parameter_suggestions(
+            proof.public_input.n_steps,
+            proof.public_input.log_cpu_rows_per_step(),
+            stark,
+        ),
+        None => vec![],
+    })
+}
+
+/// Suggests prover parameters that shrink the proof while keeping its conjectured security
+///
+/// The size of the proof grows with the number of queries, which can be lowered by raising the
+/// proof of work bits or the blowup factor, and with the size of the FRI decommitments, which
+/// depends on the FRI steps. Every FRI layer is also a step call of split Starknet proofs.
+///
+/// # Arguments
+///
+/// * `n_steps` - The number of Cairo steps of the proven program
+/// * `log_cpu_rows_per_step` - log₂ of the number of trace rows of a step of the layout
+/// * `stark` - The STARK parameters of the proof
+fn parameter_suggestions(
+    n_steps: u32,
+    log_cpu_rows_per_step: u32,
+    stark: &StarkParameters,
+) -> Vec<String> {
+    let fri = &stark.fri;
+    let (
+        Some(fri_step_list),
+        Some(n_queries),
+        Some(proof_of_work_bits),
+        Some(last_layer_degree_bound),
+        Some(log_n_cosets),
+    ) = (
+        fri.fri_step_list.clone(),
+        fri.n_queries,
+        fri.proof_of_work_bits,
+        fri.last_layer_degree_bound,
+        stark.log_n_cosets,
+    )
+    else {
+        return vec![];
+    };
+    let Ok(log_n_cosets) = u32::try_from(log_n_cosets.max(1)) else {
+        return vec![];
+    };
+    // u32 products and sums fit in u64
+    let (n_queries, proof_of_work_bits) = (u64::from(n_queries), u64::from(proof_of_work_bits));
+    let security_bits = n_queries * u64::from(log_n_cosets) + proof_of_work_bits;
+    let max_proof_of_work_bits = u64::from(MAX_SUGGESTED_PROOF_OF_WORK_BITS);
+    let mut suggestions = vec![];
+
+    if proof_of_work_bits < max_proof_of_work_bits {
+        let fewer_queries = security_bits
+            .saturating_sub(max_proof_of_work_bits)
+            .div_ceil(u64::from(log_n_cosets))
+            .max(1);
+        if fewer_queries < n_queries {
+            suggestions.push(format!(
+                "raise proof_of_work_bits from {} to {} and lower n_queries from {} to {}, keeping {} bits of security",
+                proof_of_work_bits, max_proof_of_work_bits, n_queries, fewer_queries, security_bits
+            ));
+        }
+    }
+
+    let fewer_queries = (security_bits - proof_of_work_bits)
+        .div_ceil(u64::from(log_n_cosets) + 1)
+        .max(1);
+    if fewer_queries < n_queries {
+        suggestions.push(format!(
+            "raise log_n_cosets from {} to {} and lower n_queries from {} to {}, keeping {} bits of security at the cost of doubling the proving time and memory",
+            log_n_cosets, u64::from(log_n_cosets) + 1, n_queries, fewer_queries, security_bits
+        ));
+    }
+
+    // the FRI step lists are computed for layouts with LOG_CPU_ROWS_PER_STEP rows per step, so
+    // the steps of layouts with more rows are counted as the steps that fill the same trace
+    let Some(n_steps) = log_cpu_rows_per_step
+        .checked_sub(LOG_CPU_ROWS_PER_STEP)
+        .and_then(|extra_rows_log| n_steps.checked_mul(1u32.checked_shl(extra_rows_log)?))
+    else {
+        return suggestions;
+    };
+
+    let smallest_fri_step_list = ProofSizeFriComputer
+        .compute_fri_parameters(
+            n_steps,
+            &StarkParameters {
+                fri: FriParameters {
+                    fri_step_list: None,
+                    ..fri.clone()
+                },
+                log_n_cosets: Some(log_n_cosets as i32),
+            },
+            DEFAULT_MAX_FRI_STEP,
+        )
+        .fri_step_list
+        .unwrap_or_default();
+    if smallest_fri_step_list != fri_step_list {
+        suggestions.push(format!(
+            "use fri_step_list {:?} instead of {:?}, which minimizes the size of the FRI decommitments",
+            smallest_fri_step_list, fri_step_list
+        ));
+    }
+
+    let fewest_layers =
+        compute_fri_step_list(n_steps, last_layer_degree_bound, DEFAULT_MAX_FRI_STEP);
+    if fewest_layers.len() < fri_step_list.len() && fewest_layers != smallest_fri_step_list {
+        suggestions.push(format!(
+            "use fri_step_list {:?}, whose {} FRI layers instead of {} need fewer step calls for split Starknet proofs",
+            fewest_layers,
+            fewest_layers.len(),
+            fri_step_list.len()
+        ));
+    }
+
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn stark_parameters(
+        fri_step_list: Vec<u32>,
+        n_queries: u32,
+        proof_of_work_bits: u32,
+        log_n_cosets: i32,
+    ) -> StarkParameters {
+        StarkParameters {
+            fri: FriParameters {
+                fri_step_list: Some(fri_step_list),
+                last_layer_degree_bound: Some(64),
+                n_queries: Some(n_queries),
+                proof_of_work_bits: Some(proof_of_work_bits),
+            },
+            log_n_cosets: Some(log_n_cosets),
+        }
+    }
+
+    #[rstest]
+    fn test_parameter_suggestions_queries() {
+        let suggestions = parameter_suggestions(
+            32768,
+            LOG_CPU_ROWS_PER_STEP,
+            &stark_parameters(vec![0, 4, 4, 4, 1], 18, 24, 4),
+        );
+        assert!(suggestions[0].contains("lower n_queries from 18 to 16"));
+        assert!(suggestions[1].contains("raise log_n_cosets from 4 to 5"));
+        assert!(suggestions[1].contains("lower n_queries from 18 to 15"));
+    }
+
+    #[rstest]
+    fn test_parameter_suggestions_fri_layers() {
+        let suggestions = parameter_suggestions(
+            32768,
+            LOG_CPU_ROWS_PER_STEP,
+            &stark_parameters(vec![0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1], 1, 32, 1),
+        );
+        assert!(suggestions
+            .iter()
+            .any(|suggestion| suggestion.contains("minimizes the size of the FRI decommitments")));
+        assert!(suggestions
+            .iter()
+            .all(|suggestion| !suggestion.contains("n_queries")));
+    }
+
+    #[rstest]
+    fn test_parameter_suggestions_missing_parameters() {
+        let mut stark = stark_parameters(vec![0, 4, 4, 4, 1], 18, 24, 4);
+        stark.fri.n_queries = None;
+        assert!(parameter_suggestions(32768, LOG_CPU_ROWS_PER_STEP, &stark).is_empty());
+    }
+
+    #[rstest]
+    fn test_parameter_suggestions_large_parameters() {
+        let suggestions = parameter_suggestions(
+            u32::MAX,
+            LOG_CPU_ROWS_PER_STEP + 8,
+            &stark_parameters(vec![0, 4, 4, 4, 1], u32::MAX, 24, i32::MAX),
+        );
+        assert!(suggestions[0].contains(&format!(
+            "keeping {} bits of security",
+            u64::from(u32::MAX) * u64::from(i32::MAX as u32) + 24
+        )));
+    }
+
+    #[rstest]
+    // 2^15 steps of 16 rows have a trace of 2^19 rows, folded by the FRI steps down to the last
+    // layer degree bound of 2^6
+    #[case(LOG_CPU_ROWS_PER_STEP, 13)]
+    // with 32 rows per step, as in the dynamic layout with a cpu_component_step of 2, the trace
+    // has 2^20 rows
+    #[case(LOG_CPU_ROWS_PER_STEP + 1, 14)]
+    fn test_parameter_suggestions_rows_per_step(
+        #[case] log_cpu_rows_per_step: u32,
+        #[case] sum_of_fri_steps: usize,
+    ) {
+        let fri_step_list = [vec![0], vec![1; sum_of_fri_steps]].concat();
+        let suggestions = parameter_suggestions(
+            32768,
+            log_cpu_rows_per_step,
+            &stark_parameters(fri_step_list, 1, 32, 1),
+        );
+        // the suggested step lists fold the same trace as the given one
+        let fri_suggestions: Vec<&String> = suggestions
+            .iter()
+            .filter(|suggestion| suggestion.starts_with("use fri_step_list"))
+            .collect();
+        assert!(!fri_suggestions.is_empty());
+        for suggestion in fri_suggestions {
+            let list =
+                &suggestion[suggestion.find('[').unwrap() + 1..suggestion.find(']').unwrap()];
+            let sum: usize = list
+                .split(", ")
+                .map(|step| step.parse::<usize>().unwrap())
+                .sum();
+            assert_eq!(sum, sum_of_fri_steps, "{}", suggestion);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use super::Error;
+use itertools::chain;
+use starknet_crypto::Felt;
+use swiftness_air::domains::StarkDomains;
+use swiftness_air::layout::LayoutTrait;
+use swiftness_commitment::table::decommit::table_decommit;
+use swiftness_commitment::table::types::Commitment as TableCommitment;
+use swiftness_fri::fri::{
+    fri_verify_final, fri_verify_initial, fri_verify_step, FriVerificationStateConstant,
+    FriVerificationStateVariable,
+};
+use swiftness_fri::types::{Decommitment as FriDecommitment, LayerWitness};
+use swiftness_stark::commit::stark_commit;
+use swiftness_stark::oods::{eval_oods_boundary_poly_at_points, OodsEvaluationInfo};
+use swiftness_stark::queries::{generate_queries, queries_to_points};
+use swiftness_stark::types::StarkProof;
+use swiftness_transcript::transcript::Transcript;
+
+/// Verifies a proof and returns the calldata of its `verify_proof_step` and
+/// `verify_proof_final_and_register_fact` calls
+///
+/// The steps of `StarkProof::verify` are run one by one so that the FRI verifier states passed
+/// between the calls are returned by `fri_verify_initial` and `fri_verify_step` rather than read
+/// back from the global variables swiftness records them in. Each call is the Cairo
+/// serialization of the `FriVerificationStateConstant` and `FriVerificationStateVariable` of
+/// Integrity, followed by the witness of its FRI layer, or by the coefficients of the last layer
+/// for the final call.
+///
+/// # Arguments
+///
+/// * `proof` - The proof
+/// * `security_bits` - The security bits of the proof config
+///
+/// # Returns
+///
+/// A `Result` containing the calldata of the step calls and of the final call, or an `Error` if
+/// the proof cannot be verified
+pub(super) fn fri_calls<Layout: LayoutTrait>(
+    proof: &StarkProof,
+    security_bits: Felt,
+) -> Result<(Vec<Vec<Felt>>, Vec<Felt>), Error> {
+    let verify_error = |e: &dyn std::fmt::Display| Error::Verify(e.to_string());
+    proof
+        .config
+        .validate(security_bits)
+        .map_err(|e| verify_error(&e))?;
+
+    let stark_domains = StarkDomains::new(
+        proof.config.log_trace_domain_size,
+        proof.config.log_n_cosets,
+    );
+    Layout::validate_public_input(&proof.public_input, &stark_domains)
+        .map_err(|e| verify_error(&e))?;
+
+    let digest = proof
+        .public_input
+        .get_hash(proof.config.n_verifier_friendly_commitment_layers);
+    let mut transcript = Transcript::new(digest);
+    let commitment = stark_commit::<Layout>(
+        &mut transcript,
+        &proof.public_input,
+        &proof.unsent_commitment,
+        &proof.config,
+        &stark_domains,
+    )
+    .map_err(|e| verify_error(&e))?;
+    let queries = generate_queries(
+        &mut transcript,
+        proof.config.n_queries,
+        stark_domains.eval_domain_size,
+    );
+
+    // first layer, as in `stark_verify` up to the FRI verification
+    let witness = &proof.witness;
+    Layout::traces_decommit(
+        &queries,
+        &commitment.traces,
+        &witness.traces_decommitment,
+        &witness.traces_witness,
+    )
+    .map_err(|e| verify_error(&e))?;
+    table_decommit(
+        &commitment.composition,
+        &queries,
+        &witness.composition_decommitment,
+        &witness.composition_witness,
+    )
+    .map_err(|e| verify_error(&e))?;
+    let points = queries_to_points(&queries, &stark_domains);
+    let eval_info = OodsEvaluationInfo {
+        oods_values: &commitment.oods_values,
+        oods_point: &commitment.interaction_after_composition,
+        trace_generator: &stark_domains.trace_generator,
+        constraint_coefficients: &commitment.interaction_after_oods,
+    };
+    let values = eval_oods_boundary_poly_at_points::<Layout>(
+        Layout::NUM_COLUMNS_FIRST,
+        Layout::NUM_COLUMNS_SECOND,
+        eval_info,
+        &points,
+        &witness.traces_decommitment,
+        &witness.composition_decommitment,
+    );
+
+    let (const_state, mut var_state) = fri_verify_initial(
+        &queries,
+        &commitment.fri,
+        &FriDecommitment { values, points },
+    )
+    .map_err(|e| verify_error(&e))?;
+    let const_felts = encode_const_state(&const_state);
+    let mut steps = vec![];
+    for layer in &witness.fri_witness.layers {
+        steps.push(
+            chain!(
+                const_felts.iter().copied(),
+                encode_var_state(&var_state),
+                encode_layer_witness(layer)
+            )
+            .collect(),
+        );
+        (_, var_state) = fri_verify_step(const_state.clone(), var_state.clone(), layer.clone())
+            .map_err(|e| verify_error(&e))?;
+    }
+    let final_ = chain!(
+        const_felts.iter().copied(),
+        encode_var_state(&var_state),
+        encode_array(&commitment.fri.last_layer_coefficients, 1)
+    )
+    .collect();
+    fri_verify_final(
+        const_state,
+        var_state,
+        commitment.fri.last_layer_coefficients.clone(),
+    )
+    .map_err(|e| verify_error(&e))?;
+
+    Layout::verify_public_input(&proof.public_input).map_err(|e| verify_error(&e))?;
+    Ok((steps, final_))
+}
+
+/// Encodes an array of items of `item_size` felts as its length followed by its felts
+fn encode_array(felts: &[Felt], item_size: usize) -> Vec<Felt> {
+    chain!([Felt::from(felts.len() / item_size)], felts.iter().copied()).collect()
+}
+
+fn encode_table_commitment(commitment: &TableCommitment) -> [Felt; 6] {
+    [
+        commitment.config.n_columns,
+        commitment.config.vector.height,
+        commitment
+            .config
+            .vector
+            .n_verifier_friendly_commitment_layers,
+        commitment.vector_commitment.config.height,
+        commitment
+            .vector_commitment
+            .config
+            .n_verifier_friendly_commitment_layers,
+        commitment.vector_commitment.commitment_hash,
+    ]
+}
+
+fn encode_const_state(state: &FriVerificationStateConstant) -> Vec<Felt> {
+    let commitments: Vec<Felt> = state
+        .commitment
+        .iter()
+        .flat_map(encode_table_commitment)
+        .collect();
+    chain!(
+        [Felt::from(state.n_layers)],
+        encode_array(&commitments, 6),
+        encode_array(&state.eval_points, 1),
+        encode_array(&state.step_sizes, 1),
+        [state.last_layer_coefficients_hash]
+    )
+    .collect()
+}
+
+fn encode_var_state(state: &FriVerificationStateVariable) -> Vec<Felt> {
+    let queries: Vec<Felt> = state
+        .queries
+        .iter()
+        .flat_map(|query| [query.index, query.y_value, query.x_inv_value])
+        .collect();
+    chain!([Felt::from(state.iter)], encode_array(&queries, 3)).collect()
+}
+
+fn encode_layer_witness(witness: &LayerWitness) -> Vec<Felt> {
+    chain!(
+        encode_array(&witness.leaves, 1),
+        encode_array(&witness.table_witness.vector.authentications, 1)
+    )
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_array() {
+        let felts: Vec<Felt> = (1..=6u64).map(Felt::from).collect();
+        assert_eq!(
+            encode_array(&felts, 3),
+            [2u64, 1, 2, 3, 4, 5, 6].map(Felt::from).to_vec()
+        );
+        assert_eq!(encode_array(&[], 1), vec![Felt::ZERO]);
+    }
+}
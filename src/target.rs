@@ -0,0 +1,292 @@
+use crate::args::{
+    LayoutName, Preset, ProveArgs, ProveBootloaderArgs, StoneVersion, VerifierTarget,
+};
+use crate::config::{CommitmentHash, CommitmentScheme, Hash, PowHash, ProverParametersConfig};
+use thiserror::Error;
+
+/// Layouts of the Cairo verifier deployed on Starknet by Integrity
+const INTEGRITY_LAYOUTS: &[LayoutName] = &[
+    LayoutName::dex,
+    LayoutName::recursive,
+    LayoutName::recursive_with_poseidon,
+    LayoutName::small,
+    LayoutName::starknet,
+    LayoutName::starknet_with_keccak,
+];
+
+/// Layouts of the bootloader proofs accepted by the Ethereum GPS verifier
+const ETHEREUM_LAYOUTS: &[LayoutName] = &[LayoutName::starknet];
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("The {layout} layout is not supported by the {target} verifier, supported layouts are: {supported}")]
+    UnsupportedLayout {
+        target: &'static str,
+        layout: LayoutName,
+        supported: String,
+    },
+    #[error("Stone {version} proofs are not accepted by the {target} verifier")]
+    UnsupportedStoneVersion {
+        target: &'static str,
+        version: &'static str,
+    },
+}
+
+/// Number of commitment layers that use the verifier friendly hash for Integrity, i.e. all of them
+const INTEGRITY_VERIFIER_FRIENDLY_COMMITMENT_LAYERS: u32 = 9999;
+
+impl VerifierTarget {
+    /// Layouts accepted by the verifier, or `None` if any layout is accepted
+    pub fn supported_layouts(&self) -> Option<&'static [LayoutName]> {
+        match self {
+            VerifierTarget::ethereum => Some(ETHEREUM_LAYOUTS),
+            VerifierTarget::integrity => Some(INTEGRITY_LAYOUTS),
+            VerifierTarget::stone => None,
+        }
+    }
+
+    /// Stone versions whose proofs are accepted by the verifier
+    pub fn supported_stone_versions(&self) -> &'static [StoneVersion] {
+        match self {
+            VerifierTarget::ethereum => &[StoneVersion::V5],
+            VerifierTarget::integrity | VerifierTarget::stone => {
+                &[StoneVersion::V5, StoneVersion::V6]
+            }
+        }
+    }
+}
+
+impl Preset {
+    /// The verifier the preset is meant for
+    pub fn verifier_target(&self) -> VerifierTarget {
+        match self {
+            Preset::ethereum => VerifierTarget::ethereum,
+            _ => VerifierTarget::integrity,
+        }
+    }
+
+    /// The Stone version whose proofs are accepted by the verifier
+    pub fn stone_version(&self) -> StoneVersion {
+        match self {
+            Preset::ethereum
+            | Preset::integrity_keccak160_stone5
+            | Preset::integrity_blake248_stone5 => StoneVersion::V5,
+            Preset::integrity_keccak160_stone6 | Preset::integrity_blake248_stone6 => {
+                StoneVersion::V6
+            }
+        }
+    }
+
+    /// The hash settings of the prover parameters expected by the verifier
+    ///
+    /// The GPS verifier expects the default hashes of Stone, which use Keccak for the channel
+    /// and all commitment layers, while the Integrity verifiers use Poseidon for the channel and
+    /// the commitment layers, except for the hash of the leaves which is given by the verifier
+    /// config (`keccak_160_lsb` or `blake2s_248_lsb`).
+    pub fn commitment_scheme(&self) -> CommitmentScheme {
+        match self {
+            Preset::ethereum => CommitmentScheme::stone_defaults(),
+            Preset::integrity_keccak160_stone5 | Preset::integrity_keccak160_stone6 => {
+                CommitmentScheme {
+                    channel_hash: Hash::poseidon3,
+                    commitment_hash: CommitmentHash::keccak256_masked160_lsb,
+                    pow_hash: PowHash::keccak256,
+                    n_verifier_friendly_commitment_layers:
+                        INTEGRITY_VERIFIER_FRIENDLY_COMMITMENT_LAYERS,
+                    verifier_friendly_channel_updates: true,
+                    verifier_friendly_commitment_hash: Hash::poseidon3,
+                }
+            }
+            Preset::integrity_blake248_stone5 | Preset::integrity_blake248_stone6 => {
+                CommitmentScheme {
+                    channel_hash: Hash::poseidon3,
+                    commitment_hash: CommitmentHash::blake256_masked248_lsb,
+                    pow_hash: PowHash::blake256,
+                    n_verifier_friendly_commitment_layers:
+                        INTEGRITY_VERIFIER_FRIENDLY_COMMITMENT_LAYERS,
+                    verifier_friendly_channel_updates: true,
+                    verifier_friendly_commitment_hash: Hash::poseidon3,
+                }
+            }
+        }
+    }
+
+    /// The hasher of the Integrity verifier config, e.g. `keccak_160_lsb`
+    pub fn integrity_hasher(&self) -> Option<&'static str> {
+        match self {
+            Preset::ethereum => None,
+            Preset::integrity_keccak160_stone5 | Preset::integrity_keccak160_stone6 => {
+                Some("keccak_160_lsb")
+            }
+            Preset::integrity_blake248_stone5 | Preset::integrity_blake248_stone6 => {
+                Some("blake2s_248_lsb")
+            }
+        }
+    }
+
+    /// Checks that the layout is accepted by the verifier of the preset and returns the prover
+    /// parameters with the hash settings of the preset
+    ///
+    /// # Arguments
+    ///
+    /// * `layout` - The layout used to run the program
+    /// * `parameter_config` - The prover parameters given on the command line
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the updated `ProverParametersConfig`, or an `Error` if the layout is not accepted
+    pub fn apply(
+        &self,
+        layout: &LayoutName,
+        parameter_config: ProverParametersConfig,
+    ) -> Result<ProverParametersConfig, Error> {
+        check_layout(&self.verifier_target(), layout)?;
+        Ok(parameter_config.with_commitment_scheme(self.commitment_scheme()))
+    }
+}
+
+impl ProveArgs {
+    /// Applies `--preset` to the prover parameters and the Stone version, if given
+    ///
+    /// Explicit hash arguments and `--stone_version` conflict with `--preset`, so the values of
+    /// the preset always take precedence.
+    pub fn apply_preset(&mut self) -> Result<(), Error> {
+        if let Some(preset) = &self.preset {
+            self.parameter_config =
+                preset.apply(&self.layout, std::mem::take(&mut self.parameter_config))?;
+            self.stone_version = preset.stone_version();
+        }
+        Ok(())
+    }
+}
+
+impl ProveBootloaderArgs {
+    /// Applies `--preset` to the prover parameters, the Stone version and the verifier target, if given
+    pub fn apply_preset(&mut self) -> Result<(), Error> {
+        if let Some(preset) = &self.preset {
+            self.parameter_config =
+                preset.apply(&self.layout, std::mem::take(&mut self.parameter_config))?;
+            self.stone_version = preset.stone_version();
+            self.verifier_target = preset.verifier_target();
+        }
+        Ok(())
+    }
+}
+
+/// Checks that the layout is accepted by the target verifier
+pub fn check_layout(target: &VerifierTarget, layout: &LayoutName) -> Result<(), Error> {
+    if let Some(supported_layouts) = target.supported_layouts() {
+        if !supported_layouts.contains(layout) {
+            return Err(Error::UnsupportedLayout {
+                target: target.clone().to_str(),
+                layout: layout.clone(),
+                supported: supported_layouts
+                    .iter()
+                    .map(|layout| layout.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Checks that a bootloader proof with the given layout and Stone version can be verified by the target verifier
+///
+/// # Arguments
+///
+/// * `target` - The verifier the proof is meant for
+/// * `layout` - The layout used to run the bootloader
+/// * `stone_version` - The Stone version used to prove the bootloader
+///
+/// # Returns
+///
+/// A `Result` that is `Ok` if the combination is accepted by the target, or an `Error` describing why it is not
+pub fn check_bootloader_target(
+    target: &VerifierTarget,
+    layout: &LayoutName,
+    stone_version: &StoneVersion,
+) -> Result<(), Error> {
+    check_layout(target, layout)?;
+    if !target.supported_stone_versions().contains(stone_version) {
+        return Err(Error::UnsupportedStoneVersion {
+            target: target.clone().to_str(),
+            version: stone_version.clone().to_str(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::ValueEnum;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(VerifierTarget::ethereum, LayoutName::starknet, StoneVersion::V5, true)]
+    #[case(
+        VerifierTarget::ethereum,
+        LayoutName::starknet,
+        StoneVersion::V6,
+        false
+    )]
+    #[case(
+        VerifierTarget::ethereum,
+        LayoutName::recursive,
+        StoneVersion::V5,
+        false
+    )]
+    #[case(
+        VerifierTarget::integrity,
+        LayoutName::recursive,
+        StoneVersion::V6,
+        true
+    )]
+    #[case(
+        VerifierTarget::integrity,
+        LayoutName::starknet,
+        StoneVersion::V5,
+        true
+    )]
+    #[case(
+        VerifierTarget::integrity,
+        LayoutName::dynamic,
+        StoneVersion::V6,
+        false
+    )]
+    #[case(VerifierTarget::stone, LayoutName::dynamic, StoneVersion::V6, true)]
+    fn test_check_bootloader_target(
+        #[case] target: VerifierTarget,
+        #[case] layout: LayoutName,
+        #[case] stone_version: StoneVersion,
+        #[case] expected_ok: bool,
+    ) {
+        let result = check_bootloader_target(&target, &layout, &stone_version);
+        assert_eq!(result.is_ok(), expected_ok);
+    }
+
+    #[rstest]
+    #[case(Preset::ethereum, LayoutName::starknet, true)]
+    #[case(Preset::ethereum, LayoutName::recursive, false)]
+    #[case(Preset::integrity_keccak160_stone5, LayoutName::recursive, true)]
+    #[case(Preset::integrity_blake248_stone6, LayoutName::dynamic, false)]
+    fn test_apply_preset(
+        #[case] preset: Preset,
+        #[case] layout: LayoutName,
+        #[case] expected_ok: bool,
+    ) {
+        let result = preset.apply(&layout, ProverParametersConfig::default());
+        assert_eq!(result.is_ok(), expected_ok);
+    }
+
+    #[test]
+    fn test_presets_are_accepted_by_their_target() {
+        for preset in Preset::value_variants() {
+            let target = preset.verifier_target();
+            assert!(target
+                .supported_stone_versions()
+                .contains(&preset.stone_version()));
+        }
+    }
+}
@@ -35,6 +35,15 @@ pub fn write_json_to_file<T: Serialize, P: AsRef<Path>>(
     Ok(())
 }
 
+/// Parses a field element given either as a hex string prefixed with `0x` or as a decimal string
+pub fn parse_felt(value: &str) -> Option<Felt252> {
+    let parsed = match value.strip_prefix("0x") {
+        Some(_) => Felt252::from_hex(value),
+        None => Felt252::from_dec_str(value),
+    };
+    parsed.ok()
+}
+
 pub fn cleanup_tmp_files(tmp_dir: &tempfile::TempDir) {
     if let Err(e) = std::fs::remove_dir_all(tmp_dir) {
         eprintln!("Failed to clean up temporary directory: {}", e);
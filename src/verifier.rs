@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 use crate::{
@@ -46,6 +46,20 @@ pub fn run_stone_verifier(args: VerifyArgs) -> Result<(), VerifierError> {
     Ok(())
 }
 
+/// Checks whether the Stone verifier of the given version accepts a proof
+///
+/// # Arguments
+///
+/// * `proof` - Path to the proof file
+/// * `stone_version` - The Stone version of the verifier
+///
+/// # Returns
+///
+/// `true` if the proof verifies, `false` otherwise
+pub fn accepts_proof(proof: &Path, stone_version: &StoneVersion) -> bool {
+    run_verifier_from_command_line(&proof.to_path_buf(), None, None, stone_version).is_ok()
+}
+
 fn run_verifier_from_command_line(
     in_file: &PathBuf,
     annotation_file: Option<PathBuf>,
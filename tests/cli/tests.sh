@@ -2,7 +2,6 @@
 # tests against
 # 1. https://github.com/zksecurity/stark-evm-adapter.git
 # 2. https://github.com/zksecurity/integrity.git
-# 3. https://github.com/zksecurity/integrity-calldata-generator.git -> forked because the CLI implementation also relies on the fork
 
 #!/bin/bash
 set -e
@@ -94,6 +93,8 @@ stone-cli serialize-proof \
     --proof cairo0_fibonacci_recursive_builtins_stone5_keccak_160_lsb.json \
     --network starknet \
     --serialization_type monolith \
+    --cairo_version cairo0 \
+    --format sncast \
     --output cairo0_fibonacci_recursive_builtins_stone5_keccak_160_lsb
 echo "Monolith proof serialized"
 
@@ -121,7 +122,7 @@ fi
 # Create starknet accounts directory and file if they don't exist
 mkdir -p ~/.starknet_accounts
 
-# Write account config to file (monolith proof uses `my-sepolia-account` and split proof uses `my_account`)
+# Write account config to file (both proofs are submitted with `my-sepolia-account`)
 cat > ~/.starknet_accounts/starknet_open_zeppelin_accounts.json << 'EOL'
 {
   "alpha-sepolia": {
@@ -159,36 +160,15 @@ else
     sed -i "s|url = .*|url = \"$STARKNET_SEPOLIA_V0_7_URL\"|" snfoundry.toml
 fi
 
-./verify-on-starknet.sh \
-    0x16409cfef9b6c3e6002133b61c59d09484594b37b8e4daef7dcba5495a0ef1a \
-    ../cairo0_fibonacci_recursive_builtins_stone5_keccak_160_lsb \
-    recursive \
-    keccak_160_lsb \
-    stone5 \
-    strict
+# The serialized proof starts with the verifier config (recursive, keccak_160_lsb, stone5, strict)
+INTEGRITY_ADDRESS=0x16409cfef9b6c3e6002133b61c59d09484594b37b8e4daef7dcba5495a0ef1a
+sncast --account my-sepolia-account --wait invoke \
+    --contract-address $INTEGRITY_ADDRESS \
+    --function verify_proof_full_and_register_fact \
+    --calldata $(cat ../cairo0_fibonacci_recursive_builtins_stone5_keccak_160_lsb)
 echo "Monolith proof verified on Starknet"
 
 cd $MAIN_DIR
-stone-cli serialize-proof \
-    --proof cairo0_fibonacci_recursive_builtins_stone5_keccak_160_lsb.json \
-    --network starknet \
-    --serialization_type split \
-    --output_dir split_proofs \
-    --layout recursive
-echo "Split proof serialized"
-
-# Verify split proofs using the integrity-calldata-generator repo
-cd $MAIN_DIR
-if [ ! -d "integrity-calldata-generator" ]; then
-    GIT_LFS_SKIP_SMUDGE=1 git clone https://github.com/zksecurity/integrity-calldata-generator
-    cd integrity-calldata-generator
-    git checkout b0e07df60f7b593bccb8b4d41aac7172f5582181
-    cd ..
-    echo "integrity-calldata-generator repo cloned and checked out to commit b0e07df60f7b593bccb8b4d41aac7172f5582181"
-fi
-
-# Copy split proofs to integrity-calldata-generator
-cp -r $MAIN_DIR/split_proofs/* $MAIN_DIR/integrity-calldata-generator/cli/calldata/
 
 # Generate random job id
 if [ "$(uname)" = "Linux" ]; then
@@ -198,17 +178,23 @@ elif [ "$(uname)" = "Darwin" ]; then
 fi
 echo "Generated random job id: $JOB_ID"
 
-# Replace the URL in the snfoundry.toml file
-if [ "$(uname)" = "Darwin" ]; then
-    # macOS version
-    sed -i '' "s|url = .*|url = \"$STARKNET_SEPOLIA_V0_7_URL\"|" $MAIN_DIR/integrity-calldata-generator/cli/snfoundry.toml
-else
-    # Linux version
-    sed -i "s|url = .*|url = \"$STARKNET_SEPOLIA_V0_7_URL\"|" $MAIN_DIR/integrity-calldata-generator/cli/snfoundry.toml
-fi
+stone-cli serialize-proof \
+    --proof cairo0_fibonacci_recursive_builtins_stone5_keccak_160_lsb.json \
+    --network starknet \
+    --serialization_type split \
+    --cairo_version cairo0 \
+    --format sncast \
+    --job_id $JOB_ID \
+    --output_dir split_proofs
+echo "Split proof serialized"
 
-# Run verify.sh
-cd $MAIN_DIR/integrity-calldata-generator/cli
-./verify.sh $JOB_ID recursive keccak_160_lsb stone5 strict 
+# Submit the calls in the order of the manifest, the first one includes the verifier config
+cd $MAIN_DIR/integrity/
+for CALL in $(jq -r '.calls[] | "\(.entrypoint):\(.file)"' $MAIN_DIR/split_proofs/manifest.json); do
+    sncast --account my-sepolia-account --wait invoke \
+        --contract-address $INTEGRITY_ADDRESS \
+        --function ${CALL%%:*} \
+        --calldata $(cat $MAIN_DIR/split_proofs/${CALL#*:})
+done
 
 echo "Split proof verified on Starknet"
@@ -87,3 +87,34 @@ fn test_fail_on_arg_conflicts_with_config_file(
         .failure()
         .stderr(predicate::str::contains(expected_error_message));
 }
+
+#[rstest]
+#[case("--channel_hash", "keccak256")]
+#[case("--n_verifier_friendly_commitment_layers", "0")]
+#[case("--stone_version", "v6")]
+fn test_fail_on_arg_conflicts_with_preset(
+    #[from(setup)] _path: (),
+    #[case(arg)] arg: &str,
+    #[case(value)] value: &str,
+) {
+    let program_file = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("examples")
+        .join("fibonacci.cairo");
+
+    let mut cmd = Command::cargo_bin("stone-cli").unwrap();
+    cmd.arg("prove")
+        .arg("--cairo_program")
+        .arg(&program_file)
+        .arg("--preset")
+        .arg("integrity-keccak160-stone5")
+        .arg(arg)
+        .arg(value);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "the argument '--preset <PRESET>' cannot be used with '{} <{}>'",
+            arg,
+            arg.trim_start_matches("--").to_uppercase()
+        )));
+}
@@ -1,17 +1,24 @@
+use cairo_vm::types::builtin_name::BuiltinName;
+use cairo_vm::types::relocatable::MaybeRelocatable;
+use cairo_vm::vm::runners::cairo_pie::CairoPie;
+use cairo_vm::Felt252;
 use rstest::{fixture, rstest};
 use serde::{Deserialize, Serialize};
 use std::{path::Path, str::FromStr};
-use stone_cli::cairo::run_cairo;
+use stone_cli::cairo::{run_cairo, run_cairo_to_pie, Error as CairoError};
+use stone_cli::compat::check_compat;
 use stone_cli::utils::process_args;
 use stone_cli::utils::FuncArgs;
 use stone_cli::{
     args::{
-        CairoVersion, LayoutName, Network, ProveArgs, ProveBootloaderArgs, SerializationType,
-        SerializeArgs, StoneVersion, VerifyArgs,
+        BootloaderVersion, CairoVersion, CalldataFormat, CheckCompatArgs, LayoutName,
+        MemoryVerification, Network, ProveArgs, ProveBootloaderArgs, SerializationType,
+        SerializeArgs, StoneVersion, VerifierTarget, VerifyArgs,
     },
-    bootloader::run_bootloader,
+    bootloader::{identify_bootloader, load_bootloader, run_bootloader},
     config::{ProverConfig, ProverParametersConfig},
-    serialize::serialize_proof,
+    prover::PublicInput,
+    serialize::{decode, serialize_proof, split_proof},
     verifier::run_stone_verifier,
 };
 
@@ -188,13 +195,16 @@ fn test_run_cairo0_success(
         .join(program);
     let prove_args = ProveArgs {
         cairo_version: CairoVersion::cairo0,
-        cairo_program: program_file.clone(),
+        cairo_program: Some(program_file.clone()),
+        cairo_pie: None,
         program_input: FuncArgs(vec![]),
         program_input_file: None,
         layout: LayoutName::from_str(layout).unwrap(),
         prover_config_file: None,
         parameter_file: None,
+        preset: None,
         output: tmp_dir.path().join("proof.json"),
+        cairo_pie_output: None,
         parameter_config: ProverParametersConfig::default(),
         prover_config: ProverConfig::default(),
         stone_version: StoneVersion::V5,
@@ -227,13 +237,16 @@ fn test_run_cairo1_fail(
         .join(program);
     let prove_args = ProveArgs {
         cairo_version: CairoVersion::cairo1,
-        cairo_program: program_file.clone(),
+        cairo_program: Some(program_file.clone()),
+        cairo_pie: None,
         program_input: FuncArgs(vec![]),
         program_input_file: None,
         layout: LayoutName::from_str(layout).unwrap(),
         prover_config_file: None,
         parameter_file: None,
+        preset: None,
         output: tmp_dir.path().join("proof.json"),
+        cairo_pie_output: None,
         parameter_config: ProverParametersConfig::default(),
         prover_config: ProverConfig::default(),
         stone_version: StoneVersion::V6,
@@ -248,6 +261,52 @@ fn test_run_cairo1_fail(
     }
 }
 
+// The panic data is returned by the runner in non-proof mode
+#[rstest]
+#[case("panic_short_string.cairo", "Index out of bounds")]
+#[case("panic_byte_array.cairo", "Value 1234 is too large")]
+fn test_run_cairo1_panic(
+    #[from(setup)] _path: (),
+    #[case(program)] program: &str,
+    #[case(expected_message)] expected_message: &str,
+) {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("stone-cli-test-")
+        .tempdir()
+        .expect("Failed to create temp dir");
+    let program_file = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("examples")
+        .join(program);
+    let prove_args = ProveArgs {
+        cairo_version: CairoVersion::cairo1,
+        cairo_program: Some(program_file.clone()),
+        cairo_pie: None,
+        program_input: FuncArgs(vec![]),
+        program_input_file: None,
+        layout: LayoutName::recursive,
+        prover_config_file: None,
+        parameter_file: None,
+        preset: None,
+        output: tmp_dir.path().join("proof.json"),
+        cairo_pie_output: None,
+        parameter_config: ProverParametersConfig::default(),
+        prover_config: ProverConfig::default(),
+        stone_version: StoneVersion::V6,
+        bench_memory: None,
+    };
+    let cairo_pie_output = tmp_dir.path().join("panic_pie.zip");
+    match run_cairo_to_pie(&prove_args, &cairo_pie_output, &tmp_dir) {
+        Ok(()) => panic!("Expected the program to panic"),
+        Err(e) => match e.downcast_ref::<CairoError>() {
+            Some(CairoError::Panic { felts, message }) => {
+                assert!(!felts.is_empty());
+                assert_eq!(message, expected_message);
+            }
+            _ => panic!("Expected a panic error but got: {:?}", e),
+        },
+    }
+}
+
 #[rstest]
 #[case("small", "fibonacci.cairo")]
 #[case("dex", "fibonacci.cairo")]
@@ -303,13 +362,16 @@ fn test_run_cairo1_success(
         .join(program);
     let prove_args = ProveArgs {
         cairo_version: CairoVersion::cairo1,
-        cairo_program: program_file.clone(),
+        cairo_program: Some(program_file.clone()),
+        cairo_pie: None,
         program_input: FuncArgs(vec![]),
         program_input_file: None,
         layout: LayoutName::from_str(layout).unwrap(),
         prover_config_file: None,
         parameter_file: None,
+        preset: None,
         output: tmp_dir.path().join("proof.json"),
+        cairo_pie_output: None,
         parameter_config: ProverParametersConfig::default(),
         prover_config: ProverConfig::default(),
         stone_version: StoneVersion::V6,
@@ -350,13 +412,16 @@ fn test_run_cairo1_with_input_file(
 
     let prove_args = ProveArgs {
         cairo_version: CairoVersion::cairo1,
-        cairo_program: program_file.clone(),
+        cairo_program: Some(program_file.clone()),
+        cairo_pie: None,
         program_input: FuncArgs(vec![]),
         program_input_file: Some(input_file),
         layout: LayoutName::from_str(layout).unwrap(),
         prover_config_file: None,
         parameter_file: None,
+        preset: None,
         output: tmp_dir.path().join("proof.json"),
+        cairo_pie_output: None,
         parameter_config: ProverParametersConfig::default(),
         prover_config: ProverConfig::default(),
         stone_version: StoneVersion::V6,
@@ -393,13 +458,16 @@ fn test_run_cairo1_with_inputs(
         .join(program);
     let prove_args = ProveArgs {
         cairo_version: CairoVersion::cairo1,
-        cairo_program: program_file.clone(),
+        cairo_program: Some(program_file.clone()),
+        cairo_pie: None,
         program_input: FuncArgs(process_args(input).unwrap().0),
         program_input_file: None,
         layout: LayoutName::from_str(layout).unwrap(),
         prover_config_file: None,
         parameter_file: None,
+        preset: None,
         output: tmp_dir.path().join("proof.json"),
+        cairo_pie_output: None,
         parameter_config: ProverParametersConfig::default(),
         prover_config: ProverConfig::default(),
         stone_version: StoneVersion::V6,
@@ -414,6 +482,217 @@ fn test_run_cairo1_with_inputs(
     }
 }
 
+#[rstest]
+#[case("starknet", "fibonacci_with_output.zip")]
+fn test_run_cairo_pie(
+    #[from(setup)] _path: (),
+    #[case(layout)] layout: &str,
+    #[case(cairo_pie)] cairo_pie: &str,
+) {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("stone-cli-test-")
+        .tempdir()
+        .expect("Failed to create temp dir");
+    let cairo_pie_file = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("examples")
+        .join("cairo_pie")
+        .join(cairo_pie);
+    let prove_args = ProveArgs {
+        cairo_version: CairoVersion::cairo0,
+        cairo_program: None,
+        cairo_pie: Some(cairo_pie_file),
+        program_input: FuncArgs(vec![]),
+        program_input_file: None,
+        layout: LayoutName::from_str(layout).unwrap(),
+        prover_config_file: None,
+        parameter_file: None,
+        preset: None,
+        output: tmp_dir.path().join("proof.json"),
+        cairo_pie_output: None,
+        parameter_config: ProverParametersConfig::default(),
+        prover_config: ProverConfig::default(),
+        stone_version: StoneVersion::V6,
+        bench_memory: None,
+    };
+
+    match run_cairo(&prove_args, &tmp_dir) {
+        Ok(result) => {
+            assert!(result.trace_file.exists(), "Trace file does not exist");
+            assert!(result.memory_file.exists(), "Memory file does not exist");
+            assert!(
+                result.air_public_input.exists(),
+                "AIR public input file does not exist"
+            );
+            assert!(
+                result.air_private_input.exists(),
+                "AIR private input file does not exist"
+            );
+
+            // the proved program is the bootloader running the PIE, not the PIE itself
+            let public_input: PublicInput = serde_json::from_str(
+                &std::fs::read_to_string(&result.air_public_input)
+                    .expect("Failed to read AIR public input"),
+            )
+            .expect("Failed to parse AIR public input");
+            let program_base = public_input.memory_segments["program"].begin_addr;
+            let bootloader = identify_bootloader(&public_input.public_memory, program_base)
+                .expect("Failed to identify the bootloader");
+            assert_eq!(
+                bootloader.map(|(version, _)| version),
+                Some(BootloaderVersion::v0_13_1)
+            );
+        }
+        Err(e) => panic!("Expected a successful result but got an error: {:?}", e),
+    }
+}
+
+#[rstest]
+#[case("recursive", "fibonacci_with_output.zip")]
+#[case("all_solidity", "fibonacci_with_output.zip")]
+fn test_run_cairo_pie_unsupported_layout(
+    #[from(setup)] _path: (),
+    #[case(layout)] layout: &str,
+    #[case(cairo_pie)] cairo_pie: &str,
+) {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("stone-cli-test-")
+        .tempdir()
+        .expect("Failed to create temp dir");
+    let cairo_pie_file = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("examples")
+        .join("cairo_pie")
+        .join(cairo_pie);
+    let prove_args = ProveArgs {
+        cairo_version: CairoVersion::cairo0,
+        cairo_program: None,
+        cairo_pie: Some(cairo_pie_file),
+        program_input: FuncArgs(vec![]),
+        program_input_file: None,
+        layout: LayoutName::from_str(layout).unwrap(),
+        prover_config_file: None,
+        parameter_file: None,
+        preset: None,
+        output: tmp_dir.path().join("proof.json"),
+        cairo_pie_output: None,
+        parameter_config: ProverParametersConfig::default(),
+        prover_config: ProverConfig::default(),
+        stone_version: StoneVersion::V6,
+        bench_memory: None,
+    };
+
+    // the layout lacks builtins used by the bootloader the PIE is run with
+    let error = run_cairo(&prove_args, &tmp_dir)
+        .err()
+        .expect("Expected the layout to be rejected");
+    assert!(matches!(
+        error.downcast_ref::<CairoError>(),
+        Some(CairoError::UnsupportedCairoPieLayout(_))
+    ));
+}
+
+#[rstest]
+#[case("fibonacci.json", CairoVersion::cairo0)]
+#[case("fibonacci.cairo", CairoVersion::cairo1)]
+fn test_run_cairo_to_pie(
+    #[from(setup)] _path: (),
+    #[case(program)] program: &str,
+    #[case(cairo_version)] cairo_version: CairoVersion,
+) {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("stone-cli-test-")
+        .tempdir()
+        .expect("Failed to create temp dir");
+    let program_file = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("examples")
+        .join(if cairo_version == CairoVersion::cairo0 {
+            "cairo0"
+        } else {
+            ""
+        })
+        .join(program);
+    let cairo_pie_file = tmp_dir.path().join("cairo_pie.zip");
+    let mut prove_args = ProveArgs {
+        cairo_version,
+        cairo_program: Some(program_file),
+        cairo_pie: None,
+        program_input: FuncArgs(vec![]),
+        program_input_file: None,
+        layout: LayoutName::starknet,
+        prover_config_file: None,
+        parameter_file: None,
+        preset: None,
+        output: tmp_dir.path().join("proof.json"),
+        cairo_pie_output: Some(cairo_pie_file.clone()),
+        parameter_config: ProverParametersConfig::default(),
+        prover_config: ProverConfig::default(),
+        stone_version: StoneVersion::V6,
+        bench_memory: None,
+    };
+
+    run_cairo_to_pie(&prove_args, &cairo_pie_file, &tmp_dir).expect("Failed to create Cairo PIE");
+    assert!(cairo_pie_file.exists(), "Cairo PIE file does not exist");
+
+    // the created PIE should be provable through the bootloader
+    prove_args.cairo_program = None;
+    prove_args.cairo_pie = Some(cairo_pie_file);
+    prove_args.cairo_pie_output = None;
+    run_cairo(&prove_args, &tmp_dir).expect("Failed to run the created Cairo PIE");
+}
+
+#[rstest]
+fn test_run_cairo1_to_pie_output(#[from(setup)] _path: ()) {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("stone-cli-test-")
+        .tempdir()
+        .expect("Failed to create temp dir");
+    let program_file = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("examples")
+        .join("fibonacci.cairo");
+    let cairo_pie_file = tmp_dir.path().join("cairo_pie.zip");
+    let prove_args = ProveArgs {
+        cairo_version: CairoVersion::cairo1,
+        cairo_program: Some(program_file),
+        cairo_pie: None,
+        program_input: FuncArgs(vec![]),
+        program_input_file: None,
+        layout: LayoutName::starknet,
+        prover_config_file: None,
+        parameter_file: None,
+        preset: None,
+        output: tmp_dir.path().join("proof.json"),
+        cairo_pie_output: Some(cairo_pie_file.clone()),
+        parameter_config: ProverParametersConfig::default(),
+        prover_config: ProverConfig::default(),
+        stone_version: StoneVersion::V6,
+        bench_memory: None,
+    };
+
+    run_cairo_to_pie(&prove_args, &cairo_pie_file, &tmp_dir).expect("Failed to create Cairo PIE");
+
+    // the return values must be written to the output segment of the PIE, otherwise the
+    // bootloader would commit to an empty task output
+    let cairo_pie = CairoPie::read_zip_file(&cairo_pie_file).expect("Failed to read Cairo PIE");
+    let output_segment = cairo_pie
+        .metadata
+        .builtin_segments
+        .get(&BuiltinName::output)
+        .expect("Cairo PIE has no output segment");
+    assert!(output_segment.size > 0, "Output segment is empty");
+    let mut output = cairo_pie
+        .memory
+        .0
+        .iter()
+        .filter(|((segment, _), _)| *segment as isize == output_segment.index)
+        .map(|((_, offset), value)| (*offset, value.clone()))
+        .collect::<Vec<_>>();
+    output.sort_by_key(|(offset, _)| *offset);
+    assert_eq!(output.len(), output_segment.size);
+    assert_eq!(
+        output.last().map(|(_, value)| value.clone()),
+        Some(MaybeRelocatable::from(Felt252::from(89)))
+    );
+}
+
 #[rstest]
 #[cfg(target_os = "linux")]
 #[case("small", "fibonacci.json", CairoVersion::cairo0)]
@@ -438,13 +717,16 @@ fn test_run_cairo_e2e_linux(
         .join(program);
     let prove_args = ProveArgs {
         cairo_version: cairo_version.clone(),
-        cairo_program: program_file.clone(),
+        cairo_program: Some(program_file.clone()),
+        cairo_pie: None,
         program_input: FuncArgs(vec![]),
         program_input_file: None,
         layout: LayoutName::from_str(layout).unwrap(),
         prover_config_file: None,
         parameter_file: None,
+        preset: None,
         output: tmp_dir.path().join("proof.json"),
+        cairo_pie_output: None,
         parameter_config: ProverParametersConfig::default(),
         prover_config: ProverConfig::default(),
         stone_version: StoneVersion::V6,
@@ -472,6 +754,56 @@ fn test_run_cairo_e2e_linux(
     check_tmp_files(&tmp_dir, &program_file);
 }
 
+#[rstest]
+fn test_run_stone_prover_invalid_parameter_file(#[from(setup)] _path: ()) {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("stone-cli-test-")
+        .tempdir()
+        .expect("Failed to create temp dir");
+    let program_file = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("examples")
+        .join("cairo0")
+        .join("fibonacci.json");
+    // the FRI parameters of this file are meant for a much larger bootloader trace
+    let parameter_file = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("configs")
+        .join("bootloader_cpu_air_params.json");
+    let prove_args = ProveArgs {
+        cairo_version: CairoVersion::cairo0,
+        cairo_program: Some(program_file.clone()),
+        cairo_pie: None,
+        program_input: FuncArgs(vec![]),
+        program_input_file: None,
+        layout: LayoutName::small,
+        prover_config_file: None,
+        parameter_file: Some(parameter_file),
+        preset: None,
+        output: tmp_dir.path().join("proof.json"),
+        cairo_pie_output: None,
+        parameter_config: ProverParametersConfig::default(),
+        prover_config: ProverConfig::default(),
+        stone_version: StoneVersion::V6,
+        bench_memory: None,
+    };
+
+    let run_result = run_cairo(&prove_args, &tmp_dir).expect("Failed to run cairo");
+    match run_stone_prover(
+        &prove_args,
+        &run_result.air_public_input,
+        &run_result.air_private_input,
+        &tmp_dir,
+    ) {
+        Ok(_) => panic!("Expected the parameter file to be rejected"),
+        Err(e) => assert!(
+            e.to_string().contains("stark.fri.fri_step_list"),
+            "Unexpected error: {}",
+            e
+        ),
+    }
+    assert!(!tmp_dir.path().join("proof.json").exists());
+}
+
 #[rstest]
 #[cfg(target_os = "macos")]
 #[case(
@@ -530,13 +862,16 @@ fn test_run_cairo_e2e_macos(
         .join(proof);
     let prove_args = ProveArgs {
         cairo_version: cairo_version.clone(),
-        cairo_program: program_file.clone(),
+        cairo_program: Some(program_file.clone()),
+        cairo_pie: None,
         program_input: FuncArgs(vec![]),
         program_input_file: None,
         layout: LayoutName::from_str(layout).unwrap(),
         prover_config_file: None,
         parameter_file: None,
+        preset: None,
         output: tmp_dir.path().join("proof.json"),
+        cairo_pie_output: None,
         parameter_config: ProverParametersConfig::default(),
         prover_config: ProverConfig::default(),
         stone_version: stone_version.clone(),
@@ -641,14 +976,25 @@ fn test_run_bootloader(
     let prove_bootloader_args = ProveBootloaderArgs {
         cairo_programs: program_files,
         cairo_pies: cairo_pie_files,
+        cairo1_programs: None,
+        cairo1_program_input_files: None,
+        tasks: None,
+        task_report_output: None,
         layout: LayoutName::starknet,
+        stone_version: StoneVersion::V5,
+        verifier_target: VerifierTarget::ethereum,
         prover_config_file: None,
         parameter_file: Some(bootloader_params_file.clone()),
+        preset: None,
         output: tmp_dir.path().join("bootloader_proof.json"),
         parameter_config: ProverParametersConfig::default(),
         prover_config: ProverConfig::default(),
         fact_topologies_output: tmp_dir.path().join("fact_topologies.json"),
         ignore_fact_topologies: false,
+        bootloader_version: BootloaderVersion::v0_13_1,
+        bootloader_program: None,
+        bootloader_config: None,
+        single_page: false,
         bench_memory: None,
     };
 
@@ -683,6 +1029,276 @@ fn test_run_bootloader(
     }
 }
 
+#[rstest]
+#[case(vec!["fibonacci.cairo"], None)]
+#[case(
+    vec!["with_input/array_input_sum.cairo", "with_input/branching.cairo"],
+    Some(vec!["with_input/array_input_sum_input.txt", "with_input/branching_input.txt"])
+)]
+fn test_run_bootloader_cairo1(
+    #[from(setup)] _path: (),
+    #[case(cairo1_programs)] cairo1_programs: Vec<&str>,
+    #[case(input_files)] input_files: Option<Vec<&str>>,
+) {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("stone-cli-test-")
+        .tempdir()
+        .expect("Failed to create temp dir");
+    let examples_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+
+    let bootloader_params_file = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("configs")
+        .join("bootloader_cpu_air_params.json");
+    let prove_bootloader_args = ProveBootloaderArgs {
+        cairo_programs: None,
+        cairo_pies: None,
+        cairo1_programs: Some(
+            cairo1_programs
+                .iter()
+                .map(|program| examples_dir.join(program))
+                .collect(),
+        ),
+        cairo1_program_input_files: input_files.map(|input_files| {
+            input_files
+                .iter()
+                .map(|input_file| examples_dir.join(input_file))
+                .collect()
+        }),
+        tasks: None,
+        task_report_output: None,
+        layout: LayoutName::starknet,
+        stone_version: StoneVersion::V5,
+        verifier_target: VerifierTarget::ethereum,
+        prover_config_file: None,
+        parameter_file: Some(bootloader_params_file),
+        preset: None,
+        output: tmp_dir.path().join("bootloader_proof.json"),
+        parameter_config: ProverParametersConfig::default(),
+        prover_config: ProverConfig::default(),
+        fact_topologies_output: tmp_dir.path().join("fact_topologies.json"),
+        ignore_fact_topologies: false,
+        bootloader_version: BootloaderVersion::v0_13_1,
+        bootloader_program: None,
+        bootloader_config: None,
+        single_page: false,
+        bench_memory: None,
+    };
+
+    run_bootloader(&prove_bootloader_args, &tmp_dir).expect("Failed to run bootloader");
+
+    let fact_topologies_content =
+        std::fs::read_to_string(&prove_bootloader_args.fact_topologies_output)
+            .expect("Failed to read fact_topologies file");
+    let fact_topologies: serde_json::Value = serde_json::from_str(&fact_topologies_content)
+        .expect("Failed to parse fact_topologies JSON");
+    assert_eq!(
+        fact_topologies["fact_topologies"].as_array().unwrap().len(),
+        cairo1_programs.len()
+    );
+}
+
+#[rstest]
+fn test_run_bootloader_single_page(#[from(setup)] _path: ()) {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("stone-cli-test-")
+        .tempdir()
+        .expect("Failed to create temp dir");
+    let bitwise_program = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("examples")
+        .join("cairo0")
+        .join("bitwise_output.json");
+
+    let prove_bootloader_args = ProveBootloaderArgs {
+        cairo_programs: Some(vec![bitwise_program.clone(), bitwise_program]),
+        cairo_pies: None,
+        cairo1_programs: None,
+        cairo1_program_input_files: None,
+        tasks: None,
+        task_report_output: None,
+        layout: LayoutName::starknet,
+        stone_version: StoneVersion::V5,
+        verifier_target: VerifierTarget::ethereum,
+        prover_config_file: None,
+        parameter_file: None,
+        preset: None,
+        output: tmp_dir.path().join("bootloader_proof.json"),
+        parameter_config: ProverParametersConfig::default(),
+        prover_config: ProverConfig::default(),
+        fact_topologies_output: tmp_dir.path().join("fact_topologies.json"),
+        ignore_fact_topologies: false,
+        bootloader_version: BootloaderVersion::v0_13_1,
+        bootloader_program: None,
+        bootloader_config: None,
+        single_page: true,
+        bench_memory: None,
+    };
+
+    run_bootloader(&prove_bootloader_args, &tmp_dir).expect("Failed to run bootloader");
+
+    let fact_topologies: FactTopologies = serde_json::from_str(
+        &std::fs::read_to_string(&prove_bootloader_args.fact_topologies_output)
+            .expect("Failed to read fact_topologies file"),
+    )
+    .expect("Failed to parse fact_topologies JSON");
+    assert_eq!(fact_topologies.fact_topologies.len(), 2);
+}
+
+#[rstest]
+#[case(None, true)]
+#[case(Some(vec!["0x1234567"]), false)]
+fn test_run_bootloader_task_manifest(
+    #[from(setup)] _path: (),
+    #[case(expected_output)] expected_output: Option<Vec<&str>>,
+    #[case(expected_ok)] expected_ok: bool,
+) {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("stone-cli-test-")
+        .tempdir()
+        .expect("Failed to create temp dir");
+    let examples_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+
+    let manifest_file = tmp_dir.path().join("tasks.json");
+    let manifest = serde_json::json!({
+        "tasks": [
+            {
+                "type": "program",
+                "path": examples_dir.join("cairo0").join("bitwise_output.json"),
+                "label": "bitwise",
+                "expected_output": expected_output,
+            },
+            {
+                "type": "cairo1",
+                "path": examples_dir.join("fibonacci.cairo"),
+                "label": "fibonacci",
+            },
+        ]
+    });
+    std::fs::write(&manifest_file, manifest.to_string()).expect("Failed to write manifest");
+
+    let prove_bootloader_args = ProveBootloaderArgs {
+        cairo_programs: None,
+        cairo_pies: None,
+        cairo1_programs: None,
+        cairo1_program_input_files: None,
+        tasks: Some(manifest_file),
+        task_report_output: Some(tmp_dir.path().join("task_report.json")),
+        layout: LayoutName::starknet,
+        stone_version: StoneVersion::V5,
+        verifier_target: VerifierTarget::ethereum,
+        prover_config_file: None,
+        parameter_file: None,
+        preset: None,
+        output: tmp_dir.path().join("bootloader_proof.json"),
+        parameter_config: ProverParametersConfig::default(),
+        prover_config: ProverConfig::default(),
+        fact_topologies_output: tmp_dir.path().join("fact_topologies.json"),
+        ignore_fact_topologies: false,
+        bootloader_version: BootloaderVersion::v0_13_1,
+        bootloader_program: None,
+        bootloader_config: None,
+        single_page: false,
+        bench_memory: None,
+    };
+
+    let result = run_bootloader(&prove_bootloader_args, &tmp_dir);
+    assert_eq!(result.is_ok(), expected_ok);
+
+    let task_report: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(prove_bootloader_args.task_report_output.unwrap())
+            .expect("Failed to read task report"),
+    )
+    .expect("Failed to parse task report");
+    assert_eq!(task_report[0]["label"], "bitwise");
+    assert_eq!(task_report[1]["label"], "fibonacci");
+
+    let fact_topologies: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(&prove_bootloader_args.fact_topologies_output)
+            .expect("Failed to read fact_topologies file"),
+    )
+    .expect("Failed to parse fact_topologies JSON");
+    assert_eq!(fact_topologies["fact_topologies"][0]["label"], "bitwise");
+}
+
+const SIMPLE_BOOTLOADER_PROGRAM_HASH_V0_13_1: &str =
+    "382450030162484995497251732956824096484321811411123989415157331925872358847";
+const CAIRO_VERIFIER_PROGRAM_HASHES_V0_13_1: &[&str] = &[
+    "0x97e831fcc22602fa025e89c9c6b7e7272686398de428136cf52f3f006a845e",
+    "0x7b2acdc57670aff4eac1f72b41ef759f003c122ed6cece634b76581966eade2",
+    "0x24a3890c0d0ee8f7dfed5d1f89e3991bbc1b20d506c0700b24977f16f4487",
+    "0x49904b6ecb9e083a42a1f50eb336ecc7e7a7c3ce06aabea405847cf0e2c1b2",
+    "0x64b111ddda7af6661f2d1e6255ad7576ce8281ec701b166f07debca3bd7a0eb",
+    "0x29a7e7366aa18c837867443aed5975f55107a8fdb6f33c418b81463a4156abf",
+    "0x1fbfa8a63b6197519c5fbbf3b9090b6fadea637c8afba051c7419fd1d3d7fb3",
+    "0x7d9c440b45a189c29e5d544d5b3ed167d089e3dd21e154abede91f90afb35ca",
+    "0x41e6fdf682dca5b1e1194a93da5312fe66c06f08550a62c9e27645ca3874483",
+    "0x3b58154a414a8e66fb65b1f6f612cd4ca21d68815fb0c6252930d4ddb04c72c",
+    "0x2c47af88d90c4acd90fa663713e02a1f0a8b1239882d2f6b58dc964529540c9",
+    "0x571ed7fb8805802da530fcac931794462cb7909479ec0ffd24766913a88636c",
+    "0x6ad5606d7e4e7bc01b38a36d3fdca7afcf24d5db25ed4d050a4e77c31c5527b",
+    "0x323c8251dbd935f45105bc241765a5082d9a985cbc3bffece3382708fb88dc5",
+];
+
+#[rstest]
+#[case(None, None, None, true)]
+#[case(
+    None,
+    Some(SIMPLE_BOOTLOADER_PROGRAM_HASH_V0_13_1),
+    Some(CAIRO_VERIFIER_PROGRAM_HASHES_V0_13_1),
+    true
+)]
+// the verifier hashes of the built-in bootloader must all be given
+#[case(None, Some(SIMPLE_BOOTLOADER_PROGRAM_HASH_V0_13_1), Some(&["0x1"]), false)]
+#[case(None, Some(SIMPLE_BOOTLOADER_PROGRAM_HASH_V0_13_1), Some(&CAIRO_VERIFIER_PROGRAM_HASHES_V0_13_1[1..]), false)]
+#[case(None, Some("0x1234"), Some(&["0x1"]), false)]
+#[case(Some("cairo0/fibonacci.json"), None, None, false)]
+#[case(Some("cairo0/fibonacci.json"), Some("0x1234"), Some(&["0x1"]), true)]
+#[case(Some("cairo0/fibonacci.json"), Some("0x1234"), Some(&[]), false)]
+#[case(Some("cairo0/fibonacci.json"), Some("0x1234"), Some(&["0x1", "1"]), false)]
+// a custom bootloader paired with the simple bootloader of a known version
+#[case(
+    Some("cairo0/fibonacci.json"),
+    Some(SIMPLE_BOOTLOADER_PROGRAM_HASH_V0_13_1),
+    Some(CAIRO_VERIFIER_PROGRAM_HASHES_V0_13_1),
+    true
+)]
+#[case(
+    Some("cairo0/fibonacci.json"),
+    Some(SIMPLE_BOOTLOADER_PROGRAM_HASH_V0_13_1),
+    Some(&["0x1"]),
+    false
+)]
+fn test_load_bootloader(
+    #[case(bootloader_program)] bootloader_program: Option<&str>,
+    #[case(simple_bootloader_program_hash)] simple_bootloader_program_hash: Option<&str>,
+    #[case(cairo_verifier_program_hashes)] cairo_verifier_program_hashes: Option<&[&str]>,
+    #[case(expected_ok)] expected_ok: bool,
+) {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("stone-cli-test-")
+        .tempdir()
+        .expect("Failed to create temp dir");
+    let examples_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+
+    let bootloader_program = bootloader_program.map(|program| examples_dir.join(program));
+    let bootloader_config = simple_bootloader_program_hash.map(|hash| {
+        let config_file = tmp_dir.path().join("bootloader_config.json");
+        let config = serde_json::json!({
+            "simple_bootloader_program_hash": hash,
+            "supported_cairo_verifier_program_hashes": cairo_verifier_program_hashes.unwrap_or_default(),
+        });
+        std::fs::write(&config_file, config.to_string()).expect("Failed to write config");
+        config_file
+    });
+
+    let result = load_bootloader(
+        &BootloaderVersion::v0_13_1,
+        bootloader_program.as_deref(),
+        bootloader_config.as_deref(),
+    );
+    assert_eq!(result.is_ok(), expected_ok);
+}
+
 #[rstest]
 #[case(
     "v6",
@@ -741,6 +1357,16 @@ fn test_run_serialize_ethereum(
         output: Some(serialized_proof_file.clone()),
         output_dir: None,
         serialization_type: None,
+        cairo_version: None,
+        memory_verification: None,
+        stone_version: None,
+        format: None,
+        job_id: None,
+        manifest: None,
+        split: false,
+        fact_topologies_file: None,
+        check: false,
+        report: false,
     };
 
     match run_stone_verifier(verify_args) {
@@ -765,6 +1391,91 @@ fn test_run_serialize_ethereum(
     }
 }
 
+#[rstest]
+fn test_run_serialize_ethereum_split(#[from(setup)] _path: ()) {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("stone-cli-test-")
+        .tempdir()
+        .expect("Failed to create temp dir");
+
+    let test_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("cli")
+        .join("resources")
+        .join("macos-testing-proofs");
+    let proof_file = test_dir.join("bootloader_proof.json");
+    let fact_topologies_file = test_dir.join("fact_topologies.json");
+    let annotation_file = tmp_dir.path().join("bootloader_annotation.json");
+    let extra_output_file = tmp_dir.path().join("bootloader_extra_output.json");
+    let output_dir = tmp_dir.path().join("calldata");
+
+    let verify_args = VerifyArgs {
+        proof: proof_file.clone(),
+        annotation_file: Some(annotation_file.clone()),
+        extra_output_file: Some(extra_output_file.clone()),
+        stone_version: StoneVersion::V5,
+    };
+    run_stone_verifier(verify_args).expect("Failed to run verifier");
+
+    let serialize_args = SerializeArgs {
+        proof: proof_file,
+        network: Network::ethereum,
+        layout: None,
+        annotation_file: Some(annotation_file),
+        extra_output_file: Some(extra_output_file),
+        output: Some(tmp_dir.path().join("bootloader_proof_serialized.json")),
+        output_dir: Some(output_dir.clone()),
+        serialization_type: None,
+        cairo_version: None,
+        memory_verification: None,
+        stone_version: None,
+        format: None,
+        job_id: None,
+        manifest: None,
+        split: true,
+        fact_topologies_file: Some(fact_topologies_file),
+        check: false,
+        report: true,
+    };
+    let report = serialize_proof(serialize_args)
+        .expect("Failed to serialize proof")
+        .expect("Expected a report");
+
+    let mut files: Vec<String> = std::fs::read_dir(&output_dir)
+        .expect("Failed to read output dir")
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    files.sort();
+    assert!(files[0].ends_with("_merkle_statement.calldata"));
+    assert!(files
+        .iter()
+        .any(|file| file.ends_with("_fri_statement.calldata")));
+    assert!(files
+        .iter()
+        .any(|file| file.ends_with("_memory_page_fact_registry.calldata")));
+    assert!(files
+        .last()
+        .unwrap()
+        .ends_with("_gps_statement_verifier.calldata"));
+    for file in &files {
+        let calldata = std::fs::read_to_string(output_dir.join(file)).unwrap();
+        assert!(calldata.starts_with("0x") && calldata.len() > 10);
+    }
+
+    assert_eq!(report.network, Network::ethereum);
+    assert_eq!(report.transactions.len(), files.len());
+    for (transaction, file) in report.transactions.iter().zip(&files) {
+        assert_eq!(&transaction.file, file);
+        assert!(transaction.byte_size > 0);
+        assert!(transaction.felt_count.is_none());
+        assert!(transaction.estimated_l1_gas >= 21000);
+        assert!(transaction.estimated_l2_gas.is_none());
+    }
+    // the proof uses 18 queries with 4 cosets, which a fifth coset would lower
+    assert!(!report.suggestions.is_empty());
+    assert!(report.to_string().contains("intrinsic gas"));
+}
+
 #[rstest]
 fn test_run_serialize_starknet_monolith(#[from(setup)] _path: ()) {
     let tmp_dir = tempfile::Builder::new()
@@ -791,6 +1502,17 @@ fn test_run_serialize_starknet_monolith(#[from(setup)] _path: ()) {
         output: Some(serialized_proof_file.clone()),
         output_dir: None,
         serialization_type: Some(SerializationType::monolith),
+        cairo_version: Some(CairoVersion::cairo0),
+        memory_verification: None,
+        // read from the proof by checking it with the v5 and v6 verifiers
+        stone_version: None,
+        format: None,
+        job_id: None,
+        manifest: None,
+        split: false,
+        fact_topologies_file: None,
+        check: true,
+        report: false,
     };
     serialize_proof(serialize_args).expect("Failed to serialize proof");
 
@@ -802,6 +1524,84 @@ fn test_run_serialize_starknet_monolith(#[from(setup)] _path: ()) {
     );
 }
 
+#[rstest]
+#[case(
+    CairoVersion::cairo0,
+    None,
+    None,
+    None,
+    "The proof does not record the version of the Stone prover, please pass --stone_version"
+)]
+#[case(
+    CairoVersion::cairo1,
+    Some(MemoryVerification::strict),
+    Some(StoneVersion::V5),
+    None,
+    "The strict memory verification mode is not supported for cairo1 programs"
+)]
+#[case(
+    CairoVersion::cairo0,
+    Some(MemoryVerification::cairo1),
+    Some(StoneVersion::V5),
+    None,
+    "The cairo1 memory verification mode is not supported for cairo0 programs"
+)]
+#[case(
+    CairoVersion::cairo0,
+    None,
+    Some(StoneVersion::V5),
+    Some(LayoutName::starknet),
+    "The proof uses the recursive layout but --layout is starknet"
+)]
+fn test_run_serialize_starknet_invalid_verifier_config(
+    #[from(setup)] _path: (),
+    #[case(cairo_version)] cairo_version: CairoVersion,
+    #[case(memory_verification)] memory_verification: Option<MemoryVerification>,
+    #[case(stone_version)] stone_version: Option<StoneVersion>,
+    #[case(layout)] layout: Option<LayoutName>,
+    #[case(expected)] expected: &str,
+) {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("stone-cli-test-")
+        .tempdir()
+        .expect("Failed to create temp dir");
+
+    let proof_file = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("resources")
+        .join("proofs")
+        .join("starknet")
+        .join("monolith")
+        .join("cairo0_stone5_keccak_160_lsb_example_proof.json");
+    let serialized_proof_file = tmp_dir.path().join("serialized");
+
+    let serialize_args = SerializeArgs {
+        proof: proof_file,
+        layout,
+        network: Network::starknet,
+        annotation_file: None,
+        extra_output_file: None,
+        output: Some(serialized_proof_file.clone()),
+        output_dir: None,
+        serialization_type: Some(SerializationType::monolith),
+        cairo_version: Some(cairo_version),
+        memory_verification,
+        stone_version,
+        format: None,
+        job_id: None,
+        manifest: None,
+        split: false,
+        fact_topologies_file: None,
+        check: false,
+        report: false,
+    };
+    match serialize_proof(serialize_args) {
+        Ok(_) => panic!("Expected an error but serialized the proof"),
+        Err(e) => assert_eq!(e.to_string(), expected),
+    }
+    assert!(!serialized_proof_file.exists());
+}
+
 #[rstest]
 fn test_run_serialize_starknet_split(#[from(setup)] _path: ()) {
     let tmp_dir = tempfile::Builder::new()
@@ -838,6 +1638,16 @@ fn test_run_serialize_starknet_split(#[from(setup)] _path: ()) {
         output: None,
         output_dir: Some(actual_serialized_proof_dir.clone()),
         serialization_type: Some(SerializationType::split),
+        cairo_version: Some(CairoVersion::cairo0),
+        memory_verification: None,
+        stone_version: Some(StoneVersion::V5),
+        format: None,
+        job_id: None,
+        manifest: None,
+        split: false,
+        fact_topologies_file: None,
+        check: false,
+        report: false,
     };
     serialize_proof(serialize_args).expect("Failed to serialize proof");
 
@@ -873,6 +1683,230 @@ fn test_run_serialize_starknet_split(#[from(setup)] _path: ()) {
     }
 }
 
+#[rstest]
+#[case(CalldataFormat::hex, "0x4 0x4 ")]
+#[case(CalldataFormat::sncast, "0x2a 0x4 0x4 ")]
+#[case(CalldataFormat::json, "[\"0x4\",\"0x4\",")]
+fn test_run_serialize_starknet_split_manifest(
+    #[from(setup)] _path: (),
+    #[case(format)] format: CalldataFormat,
+    #[case(step_prefix)] step_prefix: &str,
+) {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("stone-cli-test-")
+        .tempdir()
+        .expect("Failed to create temp dir");
+
+    let proof_file = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("resources")
+        .join("proofs")
+        .join("starknet")
+        .join("split")
+        .join("layouts")
+        .join("starknet")
+        .join("cairo0_example_proof.json");
+    let output_dir = tmp_dir.path().join("serialized_proofs");
+
+    let serialize_args = SerializeArgs {
+        proof: proof_file,
+        layout: None,
+        network: Network::starknet,
+        annotation_file: None,
+        extra_output_file: None,
+        output: None,
+        output_dir: Some(output_dir.clone()),
+        serialization_type: Some(SerializationType::split),
+        cairo_version: Some(CairoVersion::cairo0),
+        memory_verification: None,
+        stone_version: Some(StoneVersion::V5),
+        format: Some(format.clone()),
+        job_id: Some(42),
+        manifest: None,
+        split: false,
+        fact_topologies_file: None,
+        check: true,
+        report: true,
+    };
+    let report = serialize_proof(serialize_args)
+        .expect("Failed to serialize proof")
+        .expect("Expected a report");
+
+    let manifest: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(output_dir.join("manifest.json"))
+            .expect("Failed to read manifest"),
+    )
+    .expect("Failed to parse manifest");
+    assert_eq!(manifest["job_id"], "42");
+    assert_eq!(manifest["verifier_config"]["layout"], "starknet");
+    assert_eq!(manifest["verifier_config"]["hasher"], "keccak_160_lsb");
+    assert_eq!(manifest["format"], serde_json::json!(format));
+
+    let calls = manifest["calls"].as_array().unwrap();
+    assert_eq!(calls.first().unwrap()["file"], "initial");
+    assert_eq!(calls.first().unwrap()["entrypoint"], "verify_proof_initial");
+    assert_eq!(calls.last().unwrap()["file"], "final");
+    assert_eq!(
+        calls.last().unwrap()["entrypoint"],
+        "verify_proof_final_and_register_fact"
+    );
+    for (i, call) in calls[1..calls.len() - 1].iter().enumerate() {
+        assert_eq!(call["file"], format!("step{}", i + 1));
+        assert_eq!(call["entrypoint"], "verify_proof_step");
+    }
+    for call in calls {
+        let content = std::fs::read_to_string(output_dir.join(call["file"].as_str().unwrap()))
+            .expect("Failed to read calldata file");
+        assert_eq!(call["byte_size"], content.len());
+        assert!(call["felt_count"].as_u64().unwrap() > 0);
+    }
+
+    let step1 = std::fs::read_to_string(output_dir.join("step1")).unwrap();
+    assert!(step1.starts_with(step_prefix));
+
+    assert_eq!(report.transactions.len(), calls.len());
+    for (transaction, call) in report.transactions.iter().zip(calls) {
+        assert_eq!(transaction.file, call["file"].as_str().unwrap());
+        assert_eq!(call["byte_size"], transaction.byte_size);
+        assert!(transaction.felt_count.unwrap() > 0);
+        assert!(transaction.estimated_l2_gas.unwrap() > 0);
+    }
+}
+
+#[rstest]
+fn test_split_proof_in_parallel(#[from(setup)] _path: ()) {
+    let test_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("resources")
+        .join("proofs")
+        .join("starknet")
+        .join("split")
+        .join("layouts")
+        .join("starknet");
+    let input = std::fs::read_to_string(test_dir.join("cairo0_example_proof.json"))
+        .expect("Failed to read proof file");
+
+    let expected = split_proof(&input, LayoutName::starknet).expect("Failed to split proof");
+    let expected_final = std::fs::read_to_string(test_dir.join("serialized").join("final"))
+        .expect("Failed to read final file");
+    assert_eq!(
+        expected
+            .final_
+            .iter()
+            .map(|felt| felt.to_hex_string())
+            .collect::<Vec<_>>()
+            .join(" "),
+        expected_final
+    );
+    assert_eq!(expected.steps.len(), 4);
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let input = input.clone();
+            std::thread::spawn(move || split_proof(&input, LayoutName::starknet))
+        })
+        .collect();
+    for handle in handles {
+        let split = handle
+            .join()
+            .expect("Thread panicked")
+            .expect("Failed to split proof");
+        assert_eq!(split, expected);
+    }
+}
+
+#[rstest]
+fn test_split_two_proofs_concurrently(#[from(setup)] _path: ()) {
+    let proofs_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("resources")
+        .join("proofs")
+        .join("starknet");
+    let proofs = [
+        (
+            proofs_dir.join("split/layouts/starknet/cairo0_example_proof.json"),
+            LayoutName::starknet,
+        ),
+        (
+            proofs_dir.join("monolith/cairo0_stone5_keccak_160_lsb_example_proof.json"),
+            LayoutName::recursive,
+        ),
+    ]
+    .map(|(proof_file, layout)| {
+        let input = std::fs::read_to_string(proof_file).expect("Failed to read proof file");
+        let expected = split_proof(&input, layout.clone()).expect("Failed to split proof");
+        (input, layout, expected)
+    });
+    assert_ne!(proofs[0].2.final_, proofs[1].2.final_);
+
+    // each split must only see the states of its own proof
+    let barrier = std::sync::Arc::new(std::sync::Barrier::new(proofs.len()));
+    let handles: Vec<_> = proofs
+        .iter()
+        .cloned()
+        .map(|(input, layout, expected)| {
+            let barrier = barrier.clone();
+            std::thread::spawn(move || {
+                barrier.wait();
+                for _ in 0..3 {
+                    let split = split_proof(&input, layout.clone()).expect("Failed to split proof");
+                    assert_eq!(split, expected);
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().expect("Thread panicked");
+    }
+}
+
+#[rstest]
+fn test_check_split_fri_calls(#[from(setup)] _path: ()) {
+    let input = std::fs::read_to_string(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("resources")
+            .join("proofs")
+            .join("starknet")
+            .join("split")
+            .join("layouts")
+            .join("starknet")
+            .join("cairo0_example_proof.json"),
+    )
+    .expect("Failed to read proof file");
+    let split = split_proof(&input, LayoutName::starknet).expect("Failed to split proof");
+    let fri_calls = |steps: &[Vec<Felt252>]| {
+        let mut calls = steps.to_vec();
+        calls.push(split.final_.clone());
+        calls
+    };
+
+    decode::check_fri_calls(&input, &fri_calls(&split.steps)).expect("Failed to check the calls");
+
+    // steps sent out of order
+    let mut swapped = split.steps.clone();
+    swapped.swap(0, 1);
+    match decode::check_fri_calls(&input, &fri_calls(&swapped)) {
+        Ok(_) => panic!("Expected the swapped steps to be rejected"),
+        Err(e) => assert!(e.to_string().contains("FRI layer order"), "{}", e),
+    }
+
+    // a missing step
+    match decode::check_fri_calls(&input, &fri_calls(&split.steps[1..])) {
+        Ok(_) => panic!("Expected the missing step to be rejected"),
+        Err(e) => assert!(e.to_string().contains("FRI"), "{}", e),
+    }
+
+    // an authentication of the FRI witness changed
+    let mut tampered = split.steps.clone();
+    let last = tampered[0].len() - 1;
+    tampered[0][last] = tampered[0][last] + Felt252::ONE;
+    match decode::check_fri_calls(&input, &fri_calls(&tampered)) {
+        Ok(_) => panic!("Expected the tampered witness to be rejected"),
+        Err(e) => assert!(e.to_string().contains("FRI witness"), "{}", e),
+    }
+}
+
 fn assert_error_msg_eq(e: &anyhow::Error, expected: &str) {
     assert_eq!(e.to_string(), expected);
 }
@@ -898,3 +1932,148 @@ fn check_tmp_files(tmp_dir: &TempDir, program_file: &Path) {
         "AIR private input file does not exist"
     );
 }
+
+#[rstest]
+#[case(
+    "starknet/monolith/cairo0_stone5_keccak_160_lsb_example_proof.json",
+    Some(StoneVersion::V5),
+    &["stone-v5", "integrity-monolith", "integrity-split"]
+)]
+#[case(
+    "ethereum/layouts/starknet/bootloader_proof_v5.json",
+    Some(StoneVersion::V5),
+    &["stone-v5", "ethereum"]
+)]
+#[case(
+    "macos-testing/fibonacci_cairo0_stone_v6_proof.json",
+    None,
+&["stone-v5", "stone-v6", "integrity-split"]
+)]
+fn test_check_compat(
+    #[from(setup)] _path: (),
+    #[case(proof)] proof: &str,
+    #[case(stone_version)] stone_version: Option<StoneVersion>,
+    #[case(expected_targets)] expected_targets: &[&str],
+) {
+    let proof_file = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("resources")
+        .join("proofs")
+        .join(proof);
+    let report = check_compat(&CheckCompatArgs {
+        proof: proof_file,
+        stone_version,
+        output: None,
+    })
+    .expect("Failed to check the proof");
+
+    let compatible_targets = report
+        .targets
+        .iter()
+        .filter(|target| target.compatible)
+        .map(|target| target.target)
+        .collect::<Vec<_>>();
+    assert_eq!(compatible_targets, expected_targets);
+}
+
+fn compatible_targets(report: &stone_cli::compat::CompatReport, prefix: &str) -> Vec<&'static str> {
+    report
+        .targets
+        .iter()
+        .filter(|target| target.compatible && target.target.starts_with(prefix))
+        .map(|target| target.target)
+        .collect()
+}
+
+#[rstest]
+#[case(
+    "starknet/monolith/cairo0_stone5_keccak_160_lsb_example_proof.json",
+    LayoutName::recursive,
+    vec!["integrity-monolith", "integrity-split"]
+)]
+#[case(
+    "starknet/split/layouts/starknet/cairo0_example_proof.json",
+    LayoutName::starknet,
+    vec!["integrity-split"]
+)]
+fn test_check_compat_integrity(
+    #[from(setup)] _path: (),
+    #[case(proof)] proof: &str,
+    #[case(layout)] layout: LayoutName,
+    #[case(expected)] expected: Vec<&'static str>,
+) {
+    let proof_file = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("resources")
+        .join("proofs")
+        .join(proof);
+    let report = check_compat(&CheckCompatArgs {
+        proof: proof_file,
+        stone_version: None,
+        output: None,
+    })
+    .expect("Failed to check the proof");
+
+    assert_eq!(report.layout, layout);
+    assert!(report.bootloader_version.is_none());
+    assert_eq!(compatible_targets(&report, "integrity"), expected);
+    assert!(compatible_targets(&report, "ethereum").is_empty());
+    let split = report
+        .targets
+        .iter()
+        .find(|target| target.target == "integrity-split")
+        .unwrap();
+    assert!(split
+        .verifier_configs
+        .iter()
+        .all(|config| config.hasher == "keccak_160_lsb"));
+}
+
+#[rstest]
+#[case("tests/cli/resources/macos-testing-proofs/bootloader_proof.json")]
+#[case("tests/resources/proofs/ethereum/layouts/starknet/bootloader_proof_v5.json")]
+fn test_check_compat_bootloader(#[from(setup)] _path: (), #[case(proof)] proof: &str) {
+    let report = check_compat(&CheckCompatArgs {
+        proof: Path::new(env!("CARGO_MANIFEST_DIR")).join(proof),
+        stone_version: None,
+        output: None,
+    })
+    .expect("Failed to check the proof");
+
+    assert_eq!(report.layout, LayoutName::starknet);
+    assert_eq!(report.bootloader_version, Some(BootloaderVersion::v0_13_1));
+    assert!(compatible_targets(&report, "integrity").is_empty());
+    assert_eq!(compatible_targets(&report, "ethereum"), vec!["ethereum"]);
+}
+
+#[rstest]
+#[case(
+    "starknet/monolith/cairo0_stone5_keccak_160_lsb_example_proof.json",
+    StoneVersion::V5
+)]
+#[case("ethereum/layouts/starknet/bootloader_proof_v5.json", StoneVersion::V5)]
+#[case("ethereum/layouts/starknet/bootloader_proof_v6.json", StoneVersion::V6)]
+fn test_check_compat_infers_stone_version(
+    #[from(setup)] _path: (),
+    #[case(proof)] proof: &str,
+    #[case(expected)] expected: StoneVersion,
+) {
+    let proof_file = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("resources")
+        .join("proofs")
+        .join(proof);
+    let report = check_compat(&CheckCompatArgs {
+        proof: proof_file,
+        stone_version: None,
+        output: None,
+    })
+    .expect("Failed to check the proof");
+
+    let expected_target = match expected {
+        StoneVersion::V5 => "stone-v5",
+        StoneVersion::V6 => "stone-v6",
+    };
+    assert_eq!(report.stone_version, Some(expected));
+    assert_eq!(compatible_targets(&report, "stone"), vec![expected_target]);
+}